@@ -0,0 +1,80 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub struct Cycle {
+    start: usize,
+    length: usize,
+}
+
+impl Cycle {
+    pub fn start(&self) -> usize {
+        self.start
+    }
+    pub fn length(&self) -> usize {
+        self.length
+    }
+    /// The earliest step whose state matches the state at `step`
+    pub fn equivalent_step(&self, step: usize) -> usize {
+        match step < self.start {
+            true => step,
+            false => self.start + (step - self.start) % self.length,
+        }
+    }
+}
+
+/// Calls `next_state` for steps 0, 1, 2... until a state repeats or `max_steps` states have been seen
+pub fn detect_cycle<S, F>(mut next_state: F, max_steps: usize) -> Option<Cycle>
+where
+    S: Hash + Eq,
+    F: FnMut() -> S,
+{
+    let mut seen: HashMap<S, usize> = HashMap::new();
+    for step in 0..max_steps {
+        let state = next_state();
+        if let Some(start) = seen.get(&state) {
+            return Some(Cycle {
+                start: *start,
+                length: step - start,
+            });
+        }
+        seen.insert(state, step);
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    #[test]
+    fn test_detect_cycle() {
+        let states = [5, 3, 1, 4, 2, 1, 4, 2];
+        let mut states = states.iter();
+        let actual = detect_cycle(|| states.next().unwrap(), 100);
+        assert_eq!(actual, Some(Cycle { start: 2, length: 3 }));
+    }
+
+    #[test]
+    fn test_detect_cycle_gives_up() {
+        let mut step = 0;
+        let actual = detect_cycle(
+            || {
+                step += 1;
+                step
+            },
+            100,
+        );
+        assert_eq!(actual, None);
+    }
+
+    #[test_case(1, 1)]
+    #[test_case(2, 2)]
+    #[test_case(5, 2)]
+    #[test_case(1_000_000_000, 4)]
+    fn test_equivalent_step(step: usize, exp: usize) {
+        let cycle = Cycle { start: 2, length: 3 };
+        assert_eq!(cycle.equivalent_step(step), exp);
+    }
+}
@@ -0,0 +1,100 @@
+use std::fmt::Display;
+
+#[derive(Debug, PartialEq, Clone)]
+struct Node {
+    id: String,
+    shape: String,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+struct Edge {
+    from: String,
+    to: String,
+    label: Option<String>,
+}
+
+/// A directed graph that renders as Graphviz DOT, e.g. `dot -Tsvg graph.dot`
+#[derive(Debug, PartialEq, Clone)]
+pub struct DotGraph {
+    name: String,
+    nodes: Vec<Node>,
+    edges: Vec<Edge>,
+}
+
+impl DotGraph {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            nodes: vec![],
+            edges: vec![],
+        }
+    }
+
+    /// Adding the same id twice replaces the shape of the existing node
+    pub fn add_node(&mut self, id: &str, shape: &str) {
+        match self.nodes.iter_mut().find(|n| n.id == id) {
+            Some(node) => node.shape = shape.to_string(),
+            None => self.nodes.push(Node {
+                id: id.to_string(),
+                shape: shape.to_string(),
+            }),
+        }
+    }
+
+    pub fn add_edge(&mut self, from: &str, to: &str, label: Option<&str>) {
+        self.edges.push(Edge {
+            from: from.to_string(),
+            to: to.to_string(),
+            label: label.map(|l| l.to_string()),
+        })
+    }
+}
+
+fn quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+impl Display for DotGraph {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "digraph {} {{", quote(&self.name))?;
+        for node in &self.nodes {
+            writeln!(f, "    {} [shape={}];", quote(&node.id), node.shape)?;
+        }
+        for edge in &self.edges {
+            write!(f, "    {} -> {}", quote(&edge.from), quote(&edge.to))?;
+            match &edge.label {
+                Some(label) => writeln!(f, " [label={}];", quote(label))?,
+                None => writeln!(f, ";")?,
+            }
+        }
+        writeln!(f, "}}")
+    }
+}
+
+pub trait ToDot {
+    fn to_dot(&self) -> DotGraph;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display() {
+        let mut graph = DotGraph::new("sample");
+        graph.add_node("a", "box");
+        graph.add_node("b", "ellipse");
+        graph.add_node("a", "diamond");
+        graph.add_edge("a", "b", Some("x<\"5\""));
+        graph.add_edge("b", "a", None);
+        let expected = "\
+digraph \"sample\" {
+    \"a\" [shape=diamond];
+    \"b\" [shape=ellipse];
+    \"a\" -> \"b\" [label=\"x<\\\"5\\\"\"];
+    \"b\" -> \"a\";
+}
+";
+        assert_eq!(graph.to_string(), expected);
+    }
+}
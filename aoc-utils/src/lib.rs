@@ -1,3 +1,8 @@
+pub mod cycle;
+pub mod dot;
+pub mod parallel;
+pub mod reflection;
+
 use itertools::Itertools;
 use nom::{
     character::complete::{newline, one_of},
@@ -129,13 +134,26 @@ where
 #[derive(Default, Debug, Clone, PartialEq)]
 pub struct Collection<T>(Vec<Tile<T>>);
 impl<T> Collection<T> {
+    /// Builds a collection from tile types laid out top row first
+    pub fn from_rows(rows: Vec<Vec<T>>) -> Self {
+        let mut collection = Collection(vec![]);
+        for (row_num, row) in rows.into_iter().enumerate() {
+            for (col_num, tile) in row.into_iter().enumerate() {
+                collection.push(Tile::new(
+                    tile,
+                    Loc::new(col_num as isize, row_num as isize),
+                ));
+            }
+        }
+        collection
+    }
     fn push(&mut self, tile: Tile<T>) {
         self.0.push(tile)
     }
-    pub fn get_row(&self, row_num: isize) -> Row<T> {
+    pub fn get_row(&self, row_num: isize) -> Row<'_, T> {
         Row(self.0.iter().filter(|t| t.loc.y == row_num).collect())
     }
-    pub fn get_column(&self, col_num: isize) -> Column<T> {
+    pub fn get_column(&self, col_num: isize) -> Column<'_, T> {
         Row(self.0.iter().filter(|t| t.loc.x == col_num).collect())
     }
     pub fn count_rows(&self) -> usize {
@@ -285,6 +303,20 @@ mod tests {
         assert_eq!(unwrapped.1[1].0.len(), 63);
     }
 
+    #[test]
+    fn test_from_rows() {
+        let inp = include_str!("./data/lava_sample.txt");
+        let parsed: Collection<LavaTile> = parse_collection(inp).unwrap().1;
+        let rows = (0..parsed.count_rows() as isize)
+            .map(|y| parsed.get_row(y).into_iter().map(|t| *t.tile_type()).collect())
+            .collect();
+        let actual = Collection::from_rows(rows);
+        assert_eq!(actual.get_tile(Loc::new(8, 6)), parsed.get_tile(Loc::new(8, 6)));
+        assert_eq!(actual.count_rows(), parsed.count_rows());
+        assert_eq!(actual.count_columns(), parsed.count_columns());
+        assert_eq!(actual, parsed);
+    }
+
     #[test_case((Loc::new(1,1), Loc::new(5, 5)), vec![Loc::new(2, 2), Loc::new(3, 3), Loc::new(4, 4)])]
     #[test_case((Loc::new(1,1), Loc::new(-2, -2)), vec![Loc::new(0,0), Loc::new(-1, -1)])]
     #[test_case((Loc::new(1,1), Loc::new(-2, -5)), vec![Loc::new(0, -1), Loc::new(-1, -3)])]
@@ -0,0 +1,176 @@
+use std::iter::Sum;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+/// One worker per available core, or a single worker if that can't be determined
+pub fn default_workers() -> usize {
+    thread::available_parallelism().map_or(1, |n| n.get())
+}
+
+/// Maps `f` over `work` on at most `workers` threads, all borrowing the same read-only `shared`
+/// data. Workers pull the next item as they finish one, so uneven items balance out. Results
+/// come back in the order of `work`.
+pub fn scan<S, W, R, F>(shared: &S, work: &[W], workers: usize, f: F) -> Vec<R>
+where
+    S: Sync + ?Sized,
+    W: Sync,
+    R: Send,
+    F: Fn(&S, &W) -> R + Sync,
+{
+    let mut indexed = run_workers(work.len(), workers, |next| {
+        let mut results = vec![];
+        while let Some(i) = next() {
+            results.push((i, f(shared, &work[i])));
+        }
+        results
+    })
+    .into_iter()
+    .flatten()
+    .collect::<Vec<_>>();
+    indexed.sort_unstable_by_key(|(i, _)| *i);
+    indexed.into_iter().map(|(_, result)| result).collect()
+}
+
+/// Like `scan`, but folds the results with `reduce` as they're produced instead of collecting
+/// them. None if `work` is empty.
+pub fn scan_reduce<S, W, R, F, G>(
+    shared: &S,
+    work: &[W],
+    workers: usize,
+    f: F,
+    reduce: G,
+) -> Option<R>
+where
+    S: Sync + ?Sized,
+    W: Sync,
+    R: Send,
+    F: Fn(&S, &W) -> R + Sync,
+    G: Fn(R, R) -> R + Sync,
+{
+    run_workers(work.len(), workers, |next| {
+        let mut acc = None;
+        while let Some(i) = next() {
+            let result = f(shared, &work[i]);
+            acc = Some(match acc {
+                Some(acc) => reduce(acc, result),
+                None => result,
+            });
+        }
+        acc
+    })
+    .into_iter()
+    .flatten()
+    .reduce(&reduce)
+}
+
+pub fn scan_max<S, W, R, F>(shared: &S, work: &[W], workers: usize, f: F) -> Option<R>
+where
+    S: Sync + ?Sized,
+    W: Sync,
+    R: Send + Ord,
+    F: Fn(&S, &W) -> R + Sync,
+{
+    scan_reduce(shared, work, workers, f, R::max)
+}
+
+pub fn scan_min<S, W, R, F>(shared: &S, work: &[W], workers: usize, f: F) -> Option<R>
+where
+    S: Sync + ?Sized,
+    W: Sync,
+    R: Send + Ord,
+    F: Fn(&S, &W) -> R + Sync,
+{
+    scan_reduce(shared, work, workers, f, R::min)
+}
+
+pub fn scan_sum<S, W, R, F>(shared: &S, work: &[W], workers: usize, f: F) -> R
+where
+    S: Sync + ?Sized,
+    W: Sync,
+    R: Send + Sum,
+    F: Fn(&S, &W) -> R + Sync,
+{
+    run_workers(work.len(), workers, |next| {
+        std::iter::from_fn(next)
+            .map(|i| f(shared, &work[i]))
+            .sum::<R>()
+    })
+    .into_iter()
+    .sum()
+}
+
+/// Starts `workers` scoped threads (never more than `len`, never fewer than one),
+/// each running `worker` with a function that hands out the next unclaimed index
+fn run_workers<T, F>(len: usize, workers: usize, worker: F) -> Vec<T>
+where
+    T: Send,
+    F: Fn(&mut dyn FnMut() -> Option<usize>) -> T + Sync,
+{
+    let next_index = AtomicUsize::new(0);
+    let workers = workers.clamp(1, len.max(1));
+    thread::scope(|scope| {
+        let handles: Vec<_> = (0..workers)
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut next = || {
+                        let i = next_index.fetch_add(1, Ordering::Relaxed);
+                        (i < len).then_some(i)
+                    };
+                    worker(&mut next)
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .map(|handle| handle.join().unwrap())
+            .collect()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    #[test_case(1)]
+    #[test_case(3)]
+    #[test_case(64)]
+    fn test_scan_keeps_order(workers: usize) {
+        let offset = 10;
+        let work: Vec<usize> = (0..100).collect();
+        let actual = scan(&offset, &work, workers, |offset, w| w * 2 + offset);
+        let expected: Vec<usize> = (0..100).map(|w| w * 2 + 10).collect();
+        assert_eq!(actual, expected);
+    }
+
+    #[test_case(1)]
+    #[test_case(4)]
+    fn test_reductions(workers: usize) {
+        let shared = vec![5, 3, 9, 1, 7];
+        let work: Vec<usize> = (0..shared.len()).collect();
+        let lookup = |shared: &Vec<i32>, i: &usize| shared[*i];
+        assert_eq!(scan_max(&shared, &work, workers, lookup), Some(9));
+        assert_eq!(scan_min(&shared, &work, workers, lookup), Some(1));
+        assert_eq!(scan_sum(&shared, &work, workers, lookup), 25);
+        assert_eq!(
+            scan_reduce(&shared, &work, workers, lookup, |a, b| a * b),
+            Some(945)
+        );
+    }
+
+    #[test]
+    fn test_empty_work() {
+        let work: Vec<usize> = vec![];
+        assert_eq!(scan(&(), &work, 4, |_, w| *w), Vec::<usize>::new());
+        assert_eq!(scan_max(&(), &work, 4, |_, w| *w), None);
+        assert_eq!(scan_sum(&(), &work, 4, |_, w| *w), 0);
+    }
+
+    #[test]
+    fn test_unsized_shared() {
+        let shared: &str = "abc";
+        let work = vec![0, 2];
+        let actual = scan(shared, &work, 2, |s, i| s.as_bytes()[*i]);
+        assert_eq!(actual, vec![b'a', b'c']);
+    }
+}
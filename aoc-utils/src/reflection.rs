@@ -0,0 +1,325 @@
+use crate::{Collection, Loc};
+
+/// Which way the mirror runs: a horizontal mirror sits between two rows, a vertical one between
+/// two columns
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum Orientation {
+    Horizontal,
+    Vertical,
+}
+
+/// A candidate mirror and the tile pairs that disagree across it
+#[derive(Debug, PartialEq, Clone)]
+pub struct Reflection {
+    orientation: Orientation,
+    position: usize,
+    mismatches: Vec<(Loc, Loc)>,
+}
+
+impl Reflection {
+    pub fn orientation(&self) -> Orientation {
+        self.orientation
+    }
+    /// Number of rows above, or columns left of, the mirror
+    pub fn position(&self) -> usize {
+        self.position
+    }
+    pub fn mismatches(&self) -> &[(Loc, Loc)] {
+        &self.mismatches
+    }
+    pub fn mismatch_count(&self) -> usize {
+        self.mismatches.len()
+    }
+    pub fn is_perfect(&self) -> bool {
+        self.mismatches.is_empty()
+    }
+    /// With exactly one mismatch, the two tiles that disagree; flipping either one makes the
+    /// reflection perfect
+    pub fn smudge(&self) -> Option<(Loc, Loc)> {
+        match self.mismatches.as_slice() {
+            [pair] => Some(*pair),
+            _ => None,
+        }
+    }
+}
+
+/// Rows or columns with every tile replaced by the index of its distinct value, packed into
+/// fixed-width lanes of u64 words so two lines compare a word at a time
+struct PackedLines {
+    bits: usize,
+    lines: Vec<Vec<u64>>,
+}
+
+impl PackedLines {
+    fn new(lines: Vec<Vec<usize>>, distinct_values: usize) -> Self {
+        let bits =
+            (usize::BITS - distinct_values.saturating_sub(1).leading_zeros()).max(1) as usize;
+        let lanes = 64 / bits;
+        let lines = lines
+            .iter()
+            .map(|line| {
+                line.chunks(lanes)
+                    .map(|chunk| {
+                        chunk.iter().enumerate().fold(0, |word, (lane, value)| {
+                            word | (*value as u64) << (lane * bits)
+                        })
+                    })
+                    .collect()
+            })
+            .collect();
+        Self { bits, lines }
+    }
+
+    /// A mask with the lowest bit of every lane set
+    fn lane_mask(&self) -> u64 {
+        (0..64 / self.bits).fold(0, |mask, lane| mask | 1 << (lane * self.bits))
+    }
+
+    /// Indices along the line where `first` and `second` differ
+    fn differences(&self, first: usize, second: usize) -> impl Iterator<Item = usize> + '_ {
+        let lanes = 64 / self.bits;
+        let lane_mask = self.lane_mask();
+        self.lines[first]
+            .iter()
+            .zip(self.lines[second].iter())
+            .enumerate()
+            .flat_map(move |(word_index, (a, b))| {
+                let diff = a ^ b;
+                let mut folded = (0..self.bits).fold(0, |folded, shift| folded | diff >> shift);
+                folded &= lane_mask;
+                std::iter::from_fn(move || match folded {
+                    0 => None,
+                    _ => {
+                        let bit = folded.trailing_zeros() as usize;
+                        folded &= folded - 1;
+                        Some(word_index * lanes + bit / self.bits)
+                    }
+                })
+            })
+    }
+
+    fn count_differences(&self, first: usize, second: usize) -> usize {
+        let lane_mask = self.lane_mask();
+        self.lines[first]
+            .iter()
+            .zip(self.lines[second].iter())
+            .map(|(a, b)| {
+                let diff = a ^ b;
+                let folded = (0..self.bits).fold(0, |folded, shift| folded | diff >> shift);
+                (folded & lane_mask).count_ones() as usize
+            })
+            .sum()
+    }
+
+    /// Every mirror between two lines that needs at most `tolerance` tiles changed
+    fn reflections(&self, orientation: Orientation, tolerance: usize) -> Vec<Reflection> {
+        (1..self.lines.len())
+            .filter_map(|position| {
+                let pairs = (0..position).rev().zip(position..self.lines.len());
+                let mut count = 0;
+                for (first, second) in pairs.clone() {
+                    count += self.count_differences(first, second);
+                    if count > tolerance {
+                        return None;
+                    }
+                }
+                let mismatches = pairs
+                    .flat_map(|(first, second)| {
+                        self.differences(first, second)
+                            .map(move |index| match orientation {
+                                Orientation::Horizontal => (
+                                    Loc::new(index as isize, first as isize),
+                                    Loc::new(index as isize, second as isize),
+                                ),
+                                Orientation::Vertical => (
+                                    Loc::new(first as isize, index as isize),
+                                    Loc::new(second as isize, index as isize),
+                                ),
+                            })
+                    })
+                    .collect();
+                Some(Reflection {
+                    orientation,
+                    position,
+                    mismatches,
+                })
+            })
+            .collect()
+    }
+}
+
+impl<T: PartialEq> Collection<T> {
+    /// Mirrors between rows that need at most `tolerance` tiles changed, top to bottom
+    pub fn horizontal_reflections(&self, tolerance: usize) -> Vec<Reflection> {
+        let (rows, _, distinct_values) = self.value_indices();
+        PackedLines::new(rows, distinct_values).reflections(Orientation::Horizontal, tolerance)
+    }
+
+    /// Mirrors between columns that need at most `tolerance` tiles changed, left to right
+    pub fn vertical_reflections(&self, tolerance: usize) -> Vec<Reflection> {
+        let (_, columns, distinct_values) = self.value_indices();
+        PackedLines::new(columns, distinct_values).reflections(Orientation::Vertical, tolerance)
+    }
+
+    /// Horizontal mirrors followed by vertical ones
+    pub fn reflections(&self, tolerance: usize) -> Vec<Reflection> {
+        let (rows, columns, distinct_values) = self.value_indices();
+        let mut reflections =
+            PackedLines::new(rows, distinct_values).reflections(Orientation::Horizontal, tolerance);
+        reflections.extend(
+            PackedLines::new(columns, distinct_values)
+                .reflections(Orientation::Vertical, tolerance),
+        );
+        reflections
+    }
+
+    /// The first mirror, horizontal ones first, that needs exactly `mismatch_count` tiles changed
+    pub fn find_reflection(&self, mismatch_count: usize) -> Option<Reflection> {
+        self.reflections(mismatch_count)
+            .into_iter()
+            .find(|r| r.mismatch_count() == mismatch_count)
+    }
+
+    /// Rows and columns of the collection with every tile replaced by the index of its value
+    /// among the distinct values seen, plus how many distinct values there are
+    fn value_indices(&self) -> (Vec<Vec<usize>>, Vec<Vec<usize>>, usize) {
+        let mut rows = vec![vec![0; self.count_columns()]; self.count_rows()];
+        let mut columns = vec![vec![0; self.count_rows()]; self.count_columns()];
+        let mut distinct: Vec<&T> = vec![];
+        for tile in &self.0 {
+            let index = match distinct.iter().position(|v| **v == tile.tile_type) {
+                Some(index) => index,
+                None => {
+                    distinct.push(&tile.tile_type);
+                    distinct.len() - 1
+                }
+            };
+            let (x, y) = (tile.loc.x as usize, tile.loc.y as usize);
+            rows[y][x] = index;
+            columns[x][y] = index;
+        }
+        (rows, columns, distinct.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse_collection, parse_collection_group, BadTileTypeError, ParseableCharacters};
+    use test_case::test_case;
+
+    #[derive(Debug, PartialEq, Copy, Clone)]
+    enum LavaTile {
+        Ash,
+        Rocks,
+    }
+
+    impl TryFrom<char> for LavaTile {
+        type Error = BadTileTypeError;
+
+        fn try_from(value: char) -> Result<Self, Self::Error> {
+            match value {
+                '.' => Ok(Self::Ash),
+                '#' => Ok(Self::Rocks),
+                _ => Err(BadTileTypeError),
+            }
+        }
+    }
+    impl ParseableCharacters for LavaTile {
+        fn valid_chars() -> Vec<char> {
+            vec!['#', '.']
+        }
+    }
+
+    #[derive(Debug, PartialEq, Copy, Clone)]
+    struct Digit(char);
+
+    impl TryFrom<char> for Digit {
+        type Error = BadTileTypeError;
+
+        fn try_from(value: char) -> Result<Self, Self::Error> {
+            Ok(Self(value))
+        }
+    }
+    impl ParseableCharacters for Digit {
+        fn valid_chars() -> Vec<char> {
+            ('0'..='9').collect()
+        }
+    }
+
+    fn lava_sample() -> Vec<Collection<LavaTile>> {
+        let inp = include_str!("./data/lava_sample.txt");
+        parse_collection_group(inp).unwrap().1
+    }
+
+    #[test]
+    fn test_perfect_reflections() {
+        let valleys = lava_sample();
+        assert_eq!(valleys[0].horizontal_reflections(0), vec![]);
+        let vertical = valleys[0].vertical_reflections(0);
+        assert_eq!(vertical.len(), 1);
+        assert_eq!(vertical[0].position(), 5);
+        assert!(vertical[0].is_perfect());
+        assert_eq!(valleys[1].vertical_reflections(0), vec![]);
+        assert_eq!(valleys[1].horizontal_reflections(0)[0].position(), 4);
+    }
+
+    #[test_case(0, 0, Orientation::Vertical, 5)]
+    #[test_case(1, 0, Orientation::Horizontal, 4)]
+    #[test_case(0, 1, Orientation::Horizontal, 3)]
+    #[test_case(1, 1, Orientation::Horizontal, 1)]
+    fn test_find_reflection(
+        index: usize,
+        mismatch_count: usize,
+        exp_orientation: Orientation,
+        exp_position: usize,
+    ) {
+        let actual = lava_sample()[index]
+            .find_reflection(mismatch_count)
+            .unwrap();
+        assert_eq!(actual.orientation(), exp_orientation);
+        assert_eq!(actual.position(), exp_position);
+        assert_eq!(actual.mismatch_count(), mismatch_count);
+    }
+
+    #[test_case(0, (Loc::new(0, 0), Loc::new(0, 5)))]
+    #[test_case(1, (Loc::new(4, 0), Loc::new(4, 1)))]
+    fn test_smudge(index: usize, exp: (Loc, Loc)) {
+        let actual = lava_sample()[index].find_reflection(1).unwrap().smudge();
+        assert_eq!(actual, Some(exp));
+    }
+
+    #[test]
+    fn test_tolerance_includes_every_axis() {
+        let valley = &lava_sample()[0];
+        let all = valley.reflections(usize::MAX);
+        assert_eq!(all.len(), 6 + 8);
+        assert!(all.iter().all(|r| r.mismatch_count() <= 9 * 3));
+    }
+
+    #[test]
+    fn test_many_distinct_values() {
+        let inp = "0123443210\n5678998765\n0123443217\n";
+        let collection: Collection<Digit> = parse_collection(inp).unwrap().1;
+        let vertical = collection.vertical_reflections(1);
+        assert_eq!(vertical.len(), 1);
+        assert_eq!(vertical[0].position(), 5);
+        assert_eq!(vertical[0].smudge(), Some((Loc::new(0, 2), Loc::new(9, 2))));
+    }
+
+    #[test]
+    fn test_rows_wider_than_a_word() {
+        let row: String = (0..100)
+            .map(|i| if i % 3 == 0 { '#' } else { '.' })
+            .collect();
+        let mut changed = row.clone();
+        changed.replace_range(70..71, if &row[70..71] == "#" { "." } else { "#" });
+        let inp = format!("{row}\n{changed}\n");
+        let collection: Collection<LavaTile> = parse_collection(&inp).unwrap().1;
+        let horizontal = collection.horizontal_reflections(1);
+        assert_eq!(
+            horizontal[0].smudge(),
+            Some((Loc::new(70, 0), Loc::new(70, 1)))
+        );
+    }
+}
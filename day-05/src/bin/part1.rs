@@ -1,4 +1,5 @@
 mod shared;
+use aoc_utils::parallel::{default_workers, scan_min};
 use shared::{Almanac, ItemType};
 
 fn main() {
@@ -10,10 +11,10 @@ fn main() {
 
 fn get_lowest_seed_location(almanac: &Almanac) -> usize {
     let seeds = almanac.seeds();
-    seeds
-        .iter()
-        .map(|seed| almanac.convert(&ItemType::Seed, &ItemType::Location, *seed))
-        .min().unwrap()
+    scan_min(almanac, &seeds, default_workers(), |almanac, seed| {
+        almanac.convert(&ItemType::Seed, &ItemType::Location, *seed)
+    })
+    .unwrap()
 }
 
 #[cfg(test)]
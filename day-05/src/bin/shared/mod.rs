@@ -50,9 +50,6 @@ pub struct Almanac {
 }
 
 impl Almanac {
-    fn new(seeds: Vec<Seed>, converters: Vec<Converter>) -> Self {
-        Self { seeds, converters }
-    }
     pub fn convert(&self, from_type: &ItemType, to_type: &ItemType, value: usize) -> usize {
         if from_type == to_type {
             return value;
@@ -166,7 +163,7 @@ mod tests {
     fn test_parse_almanac() {
         let inp = include_str!("../../data/sample_input.txt");
         let actual = Almanac::from(inp);
-        assert_eq!(actual.seeds, vec![79, 14, 55, 13]);
+        assert_eq!(actual.seeds(), vec![79, 14, 55, 13]);
         let expected_converter = Converter::new(ItemType::Seed, ItemType::Soil, 50, 98, 2);
         assert_eq!(actual.converters.len(), 18);
         assert_eq!(actual.converters[0], expected_converter);
@@ -1,5 +1,11 @@
 mod shared;
+use shared::parse_map;
 
 fn main() {
-
+    let inp = include_str!("../data/puzzle_input.txt");
+    let map = parse_map(inp).unwrap();
+    let out = map
+        .ghost_traverse_steps(|n| n.ends_with('A'), |n| n.ends_with('Z'))
+        .unwrap();
+    println!("{}", out);
 }
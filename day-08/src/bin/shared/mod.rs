@@ -1,8 +1,9 @@
+use num::integer::{ExtendedGcd, Integer};
 use std::collections::HashMap;
 
 use nom::{
     bytes::complete::tag,
-    character::complete::{alpha1, newline, one_of},
+    character::complete::{alphanumeric1, newline, one_of},
     combinator::map,
     multi::many1,
     sequence::{separated_pair, terminated, tuple},
@@ -22,13 +23,13 @@ impl<'a> Coordinate<'a> {
     }
 }
 
-/// parses "AAA = (BBB, BBB)"
-fn parse_coordinate(inp: &str) -> IResult<&str, Coordinate> {
+/// parses "AAA = (BBB, BBB)" or "11A = (11B, XXX)"
+fn parse_coordinate(inp: &str) -> IResult<&str, Coordinate<'_>> {
     let (inp, coordinate): (&str, Coordinate) = map(
         separated_pair(
-            alpha1,
+            alphanumeric1,
             tag(" = ("),
-            terminated(tuple((alpha1, tag(", "), alpha1)), tag(")")),
+            terminated(tuple((alphanumeric1, tag(", "), alphanumeric1)), tag(")")),
         ),
         |(loc, (left, _, right))| Coordinate::new(loc, left, right),
     )(inp)?;
@@ -40,57 +41,197 @@ fn parse_directions(inp: &str) -> IResult<&str, Directions> {
     many1(one_of("LR"))(inp)
 }
 
+#[derive(Debug, PartialEq)]
+pub enum MapError {
+    Parse(String),
+    DuplicateNode(String),
+    UnknownNode { node: String, referenced_by: String },
+}
+
+/// Nodes are interned to ids in the order they are defined
 pub struct Map<'a> {
     directions: Directions,
-    database: HashMap<&'a str, Coordinate<'a>>,
+    names: Vec<&'a str>,
+    left: Vec<usize>,
+    right: Vec<usize>,
 }
 
 impl<'a> Map<'a> {
-    fn new(directions: Directions, coordinates: Vec<Coordinate<'a>>) -> Self {
-        let mut database = HashMap::new();
-        coordinates.iter().for_each(|c| {
-            database.insert(c.loc, c.clone());
-        });
-        Self {
+    fn new(directions: Directions, coordinates: Vec<Coordinate<'a>>) -> Result<Self, MapError> {
+        let mut ids = HashMap::new();
+        for (id, coordinate) in coordinates.iter().enumerate() {
+            if ids.insert(coordinate.loc, id).is_some() {
+                return Err(MapError::DuplicateNode(coordinate.loc.to_string()));
+            }
+        }
+        let lookup = |node: &str, referenced_by: &str| {
+            ids.get(node).copied().ok_or(MapError::UnknownNode {
+                node: node.to_string(),
+                referenced_by: referenced_by.to_string(),
+            })
+        };
+        let mut left = Vec::with_capacity(coordinates.len());
+        let mut right = Vec::with_capacity(coordinates.len());
+        for coordinate in &coordinates {
+            left.push(lookup(coordinate.left, coordinate.loc)?);
+            right.push(lookup(coordinate.right, coordinate.loc)?);
+        }
+        Ok(Self {
             directions,
-            database,
+            names: coordinates.iter().map(|c| c.loc).collect(),
+            left,
+            right,
+        })
+    }
+
+    fn id(&self, name: &str) -> Option<usize> {
+        self.names.iter().position(|n| *n == name)
+    }
+
+    fn next_id(&self, id: usize, direction: char) -> usize {
+        match direction {
+            'L' => self.left[id],
+            'R' => self.right[id],
+            _ => unreachable!(),
+        }
+    }
+
+    /// Walks from `start` until it is back in a state it has already been in.
+    /// Steps are counted from `start` itself, so a start that is an end is hit at step 0.
+    fn walk(&self, start: usize, is_end: &[bool]) -> Walker {
+        let mut seen = vec![None; self.names.len() * self.directions.len()];
+        let mut end_steps = vec![];
+        let mut current_id = start;
+        let mut step = 0;
+        loop {
+            let direction_idx = step % self.directions.len();
+            let state = current_id * self.directions.len() + direction_idx;
+            if let Some(cycle_start) = seen[state] {
+                return Walker {
+                    cycle_start,
+                    cycle_length: step - cycle_start,
+                    end_steps,
+                };
+            }
+            seen[state] = Some(step);
+            if is_end[current_id] {
+                end_steps.push(step);
+            }
+            current_id = self.next_id(current_id, self.directions[direction_idx]);
+            step += 1;
         }
     }
 
-    fn search(&self, point: &str) -> &Coordinate {
-        self.database.get(point).unwrap()
-    }
-
-    pub fn traverse_steps(&self, desired: &str) -> usize {
-        let mut current_coordinate = self.search("AAA");
-        let mut steps = 0;
-        while current_coordinate.loc != desired {
-            self.directions.iter().for_each(|c| match c {
-                'L' => {
-                    current_coordinate = self.search(current_coordinate.left);
-                    steps += 1
-                }
-                'R' => {
-                    current_coordinate = self.search(current_coordinate.right);
-                    steps += 1
-                }
-                _ => unreachable!(),
-            });
+    fn ends<F>(&self, is_end: F) -> Vec<bool>
+    where
+        F: Fn(&str) -> bool,
+    {
+        self.names.iter().map(|n| is_end(n)).collect()
+    }
+
+    /// Steps until the first time `is_end` holds, or `None` if it never will
+    #[allow(dead_code)]
+    pub fn traverse_steps<F>(&self, start: &str, is_end: F) -> Option<usize>
+    where
+        F: Fn(&str) -> bool,
+    {
+        let start = self.id(start)?;
+        self.walk(start, &self.ends(is_end))
+            .end_steps
+            .first()
+            .copied()
+    }
+
+    /// Steps until every walker, one per `is_start` node, is on an `is_end` node at once
+    #[allow(dead_code)]
+    pub fn ghost_traverse_steps<S, E>(&self, is_start: S, is_end: E) -> Option<usize>
+    where
+        S: Fn(&str) -> bool,
+        E: Fn(&str) -> bool,
+    {
+        let ends = self.ends(is_end);
+        let walkers: Vec<Walker> = (0..self.names.len())
+            .filter(|id| is_start(self.names[*id]))
+            .map(|id| self.walk(id, &ends))
+            .collect();
+        // A step before every walker has entered its cycle can't be found from the congruences,
+        // since a walker may still be ending at steps it never comes back to
+        let latest_cycle_start = walkers.iter().map(|w| w.cycle_start).max()?;
+        let early = (0..latest_cycle_start).find(|step| walkers.iter().all(|w| w.is_end_at(*step)));
+        if early.is_some() {
+            return early;
         }
-        steps
+
+        let mut candidates = vec![(0, 1)];
+        for walker in &walkers {
+            let cycle_end_steps: Vec<usize> = walker
+                .end_steps
+                .iter()
+                .copied()
+                .filter(|step| *step >= walker.cycle_start)
+                .collect();
+            candidates = candidates
+                .iter()
+                .flat_map(|candidate| {
+                    cycle_end_steps.iter().filter_map(|step| {
+                        combine_congruences(*candidate, (*step, walker.cycle_length))
+                    })
+                })
+                .collect();
+        }
+        candidates
+            .into_iter()
+            .map(
+                |(remainder, modulus)| match remainder < latest_cycle_start {
+                    true => {
+                        remainder + (latest_cycle_start - remainder).div_ceil(modulus) * modulus
+                    }
+                    false => remainder,
+                },
+            )
+            .min()
     }
 }
 
+#[derive(Debug, PartialEq)]
+struct Walker {
+    cycle_start: usize,
+    cycle_length: usize,
+    end_steps: Vec<usize>,
+}
+
+impl Walker {
+    fn is_end_at(&self, step: usize) -> bool {
+        let step = match step < self.cycle_start {
+            true => step,
+            false => self.cycle_start + (step - self.cycle_start) % self.cycle_length,
+        };
+        self.end_steps.contains(&step)
+    }
+}
+
+/// Merges `x = a (mod m)` and `x = b (mod n)` into a single congruence, reduced to its smallest remainder.
+/// When every walker ends only at multiples of its cycle length this is just the LCM.
+fn combine_congruences((a, m): (usize, usize), (b, n): (usize, usize)) -> Option<(usize, usize)> {
+    let (a, m, b, n) = (a as i128, m as i128, b as i128, n as i128);
+    let ExtendedGcd { gcd, x, .. } = m.extended_gcd(&n);
+    if (b - a) % gcd != 0 {
+        return None;
+    }
+    let lcm = m / gcd * n;
+    let remainder = (a + m * ((b - a) / gcd * x).rem_euclid(n / gcd)).rem_euclid(lcm);
+    Some((remainder as usize, lcm as usize))
+}
+
 type Directions = Vec<char>;
-pub fn parse_map(inp: &str) -> IResult<&str, Map> {
-    map(
-        separated_pair(
-            parse_directions,
-            many1(newline),
-            many1(terminated(parse_coordinate, newline)),
-        ),
-        |(directions, coordinates)| Map::new(directions, coordinates),
+pub fn parse_map(inp: &str) -> Result<Map<'_>, MapError> {
+    let (_, (directions, coordinates)) = separated_pair(
+        parse_directions,
+        many1(newline),
+        many1(terminated(parse_coordinate, newline)),
     )(inp)
+    .map_err(|e| MapError::Parse(e.to_string()))?;
+    Map::new(directions, coordinates)
 }
 
 #[cfg(test)]
@@ -101,11 +242,13 @@ mod tests {
     #[test_case("AAA = (BBB, BBB)", ("", Coordinate::new("AAA", "BBB", "BBB")); "AAA")]
     #[test_case("BBB = (AAA, ZZZ)", ("", Coordinate::new("BBB", "AAA", "ZZZ")); "BBB")]
     #[test_case("ZZZ = (ZZZ, ZZZ)", ("", Coordinate::new("ZZZ", "ZZZ", "ZZZ")); "ZZZ")]
+    #[test_case("11A = (11B, XXX)", ("", Coordinate::new("11A", "11B", "XXX")); "Alphanumeric")]
     fn test_parse_coordinate(inp: &str, exp: (&str, Coordinate)) {
         let actual = parse_coordinate(inp);
         assert_eq!(actual, Ok(exp));
     }
 
+    #[test]
     fn test_parse_directions() {
         let actual = parse_directions("LRLRLRLLLRRR");
         let expected = Ok((
@@ -118,8 +261,51 @@ mod tests {
     #[test]
     fn test_parse_map() {
         let inp = include_str!("../../data/sample_input.txt");
-        let map = parse_map(inp).unwrap().1;
-        assert_eq!(map.search("BBB"), &Coordinate::new("BBB", "AAA", "ZZZ"));
-        assert_eq!(map.traverse_steps("ZZZ"), 6)
+        let map = parse_map(inp).unwrap();
+        assert_eq!(map.names, vec!["AAA", "BBB", "ZZZ"]);
+        assert_eq!((map.left[1], map.right[1]), (0, 2));
+        assert_eq!(map.traverse_steps("AAA", |n| n == "ZZZ"), Some(6))
+    }
+
+    #[test_case("LLR\n\nAAA = (ZZZ, BBB)\nBBB = (BBB, BBB)\nZZZ = (ZZZ, ZZZ)\n", "AAA", Some(1); "Ends mid pass")]
+    #[test_case("RL\n\nAAA = (AAA, BBB)\nBBB = (AAA, BBB)\nZZZ = (ZZZ, ZZZ)\n", "AAA", None; "Unreachable")]
+    #[test_case("L\n\nZZZ = (AAA, AAA)\nAAA = (ZZZ, ZZZ)\n", "ZZZ", Some(0); "Starts at end")]
+    fn test_traverse_steps(inp: &str, start: &str, exp: Option<usize>) {
+        let map = parse_map(inp).unwrap();
+        assert_eq!(map.traverse_steps(start, |n| n == "ZZZ"), exp);
+    }
+
+    #[test_case("L\n\nAAA = (BBB, AAA)\n", MapError::UnknownNode { node: "BBB".to_string(), referenced_by: "AAA".to_string() }; "Unknown")]
+    #[test_case("L\n\nAAA = (AAA, AAA)\nAAA = (AAA, AAA)\n", MapError::DuplicateNode("AAA".to_string()); "Duplicate")]
+    fn test_parse_map_errors(inp: &str, exp: MapError) {
+        assert_eq!(parse_map(inp).err(), Some(exp));
+    }
+
+    #[test]
+    fn test_ghost_traverse_steps() {
+        let inp = include_str!("../../data/sample_input2.txt");
+        let map = parse_map(inp).unwrap();
+        let actual = map.ghost_traverse_steps(|n| n.ends_with('A'), |n| n.ends_with('Z'));
+        assert_eq!(actual, Some(6));
+    }
+
+    #[test_case("L\n\nPPP = (PPP, PPP)\nQQA = (QQB, QQB)\nQQB = (QQZ, QQZ)\nQQZ = (QQD, QQD)\nQQD = (QQD, QQD)\n"; "Looping walker first")]
+    #[test_case("L\n\nQQA = (QQB, QQB)\nQQB = (QQZ, QQZ)\nQQZ = (QQD, QQD)\nQQD = (QQD, QQD)\nPPP = (PPP, PPP)\n"; "Looping walker last")]
+    fn test_ghost_traverse_steps_before_cycle(inp: &str) {
+        let map = parse_map(inp).unwrap();
+        let actual =
+            map.ghost_traverse_steps(|n| n == "PPP" || n == "QQA", |n| n == "PPP" || n == "QQZ");
+        assert_eq!(actual, Some(2));
+    }
+
+    #[test_case((0, 2), (0, 3), Some((0, 6)); "Lcm")]
+    #[test_case((1, 4), (3, 6), Some((9, 12)); "Shared factor")]
+    #[test_case((1, 4), (2, 6), None; "No solution")]
+    fn test_combine_congruences(
+        first: (usize, usize),
+        second: (usize, usize),
+        exp: Option<(usize, usize)>,
+    ) {
+        assert_eq!(combine_congruences(first, second), exp);
     }
 }
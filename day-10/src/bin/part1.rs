@@ -3,9 +3,9 @@ use shared::{parser::parse_network, Path};
 
 fn main() {
     let inp = include_str!("../data/puzzle_input.txt");
-    let network = parse_network(inp).unwrap().1;
-    let starting_tile = network.get_starting_tile().unwrap();
-    let connected_path = network.get_connected_paths(starting_tile, starting_tile, None, Path::default());
+    let mut network = parse_network(inp).unwrap().1;
+    network.resolve_start().unwrap();
+    let connected_path = network.get_loop().unwrap();
     println!("{}", get_furthest_point(&connected_path));
 }
 
@@ -21,19 +21,18 @@ mod tests {
     #[test]
     fn get_furthest_from_starting() {
         let inp = include_str!("../data/sample_input.txt");
-        let network = parse_network(inp).unwrap().1;
-        let starting_tile = network.get_starting_tile().unwrap();
-        let connected_path = network.get_connected_paths(starting_tile, starting_tile, None, Path::default());
+        let mut network = parse_network(inp).unwrap().1;
+        network.resolve_start().unwrap();
+        let connected_path = network.get_loop().unwrap();
         assert_eq!(get_furthest_point(&connected_path), 4);
     }
 
     #[test]
     fn get_furthest_from_starting_sample_2() {
         let inp = include_str!("../data/sample_input2.txt");
-        let network = parse_network(inp).unwrap().1;
-        let starting_tile = network.get_starting_tile().unwrap();
-        let connected_path = network.get_connected_paths(starting_tile, starting_tile, None, Path::default());
+        let mut network = parse_network(inp).unwrap().1;
+        network.resolve_start().unwrap();
+        let connected_path = network.get_loop().unwrap();
         assert_eq!(get_furthest_point(&connected_path), 8);
     }
-
 }
@@ -1,5 +1,15 @@
 mod shared;
+use shared::parser::parse_network;
 
 fn main() {
-
+    let inp = include_str!("../data/puzzle_input.txt");
+    let mut network = parse_network(inp).unwrap().1;
+    network.resolve_start().unwrap();
+    let connected_path = network.get_loop().unwrap();
+    if std::env::args().any(|a| a == "--render") {
+        print!("{}", network.render(&connected_path, true));
+    }
+    let enclosed = network.enclosed_tiles(&connected_path);
+    debug_assert_eq!(enclosed.len(), connected_path.enclosed_count());
+    println!("{}", enclosed.len());
 }
@@ -1,5 +1,7 @@
 pub mod parser;
 
+use std::collections::HashSet;
+
 #[derive(Debug)]
 struct BadTileError;
 
@@ -7,7 +9,7 @@ struct BadTileError;
 struct BadRowError;
 
 #[derive(Debug, PartialEq, Copy, Clone)]
-enum Pipe {
+pub enum Pipe {
     Vertical,
     Horizontal,
     NorthEastBend,
@@ -99,6 +101,49 @@ impl Tile {
     }
 }
 
+impl Pipe {
+    fn connects_north(&self) -> bool {
+        matches!(
+            self,
+            Pipe::Vertical | Pipe::NorthEastBend | Pipe::NorthWestBend
+        )
+    }
+
+    /// The pipe at `loc` that joins the two neighbouring locations
+    fn connecting(loc: Loc, first: Loc, second: Loc) -> Option<Self> {
+        let connects = |neighbor: Loc| first == neighbor || second == neighbor;
+        match (
+            connects(loc.north()),
+            connects(loc.south()),
+            connects(loc.east()),
+            connects(loc.west()),
+        ) {
+            (true, true, false, false) => Some(Pipe::Vertical),
+            (false, false, true, true) => Some(Pipe::Horizontal),
+            (true, false, true, false) => Some(Pipe::NorthEastBend),
+            (true, false, false, true) => Some(Pipe::NorthWestBend),
+            (false, true, false, true) => Some(Pipe::SouthWestBend),
+            (false, true, true, false) => Some(Pipe::SouthEastBend),
+            _ => None,
+        }
+    }
+}
+
+impl Tile {
+    fn box_drawing(&self) -> char {
+        match self {
+            Tile::Ground => '.',
+            Tile::Pipe(Pipe::Vertical) => '│',
+            Tile::Pipe(Pipe::Horizontal) => '─',
+            Tile::Pipe(Pipe::NorthEastBend) => '└',
+            Tile::Pipe(Pipe::NorthWestBend) => '┘',
+            Tile::Pipe(Pipe::SouthWestBend) => '┐',
+            Tile::Pipe(Pipe::SouthEastBend) => '┌',
+            Tile::StartingPosition => 'S',
+        }
+    }
+}
+
 impl TryFrom<&char> for Tile {
     type Error = BadTileError;
 
@@ -142,8 +187,8 @@ impl NetworkedRow {
     }
 }
 
-#[derive(Debug, PartialEq, Clone, Copy)]
-struct Loc {
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub struct Loc {
     x: i32,
     y: i32,
 }
@@ -190,21 +235,49 @@ impl Path {
     pub fn len(&self) -> usize {
         self.networked_tiles.len()
     }
+
+    /// Tiles strictly inside the loop, using the Shoelace formula and Pick's theorem
+    #[allow(dead_code)]
+    pub fn enclosed_count(&self) -> usize {
+        let vertices: Vec<Loc> = self.networked_tiles.iter().map(|t| t.location).collect();
+        let double_area: i64 = vertices
+            .iter()
+            .zip(vertices.iter().cycle().skip(1))
+            .map(|(a, b)| a.x as i64 * b.y as i64 - b.x as i64 * a.y as i64)
+            .sum::<i64>()
+            .abs();
+        (double_area as usize + 2 - self.len()) / 2
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum LoopError {
+    NoStart,
+    DisconnectedStart(Loc),
+    AmbiguousStart(Loc),
+    BrokenLoop(Loc),
 }
 
 pub struct Network {
     rows: Vec<NetworkedRow>,
+    start: Option<Loc>,
 }
 
 impl Network {
     fn new(rows: Vec<Row>) -> Self {
-        let networked_rows: Vec<_> = rows
+        let networked_rows: Vec<NetworkedRow> = rows
             .iter()
             .enumerate()
-            .map(|(row_num, naive_row)| (NetworkedRow::new(row_num as u8, naive_row.clone())))
+            .map(|(row_num, naive_row)| NetworkedRow::new(row_num as u8, naive_row.clone()))
             .collect();
+        let start = networked_rows
+            .iter()
+            .flat_map(|row| row.0.iter())
+            .find(|t| t.tile == Tile::StartingPosition)
+            .map(|t| t.location);
         Self {
             rows: networked_rows,
+            start,
         }
     }
 
@@ -212,44 +285,138 @@ impl Network {
         self.rows.get(loc.y as usize)?.0.get(loc.x as usize)
     }
 
+    fn set_tile(&mut self, loc: Loc, tile: Tile) {
+        self.rows[loc.y as usize].0[loc.x as usize].tile = tile;
+    }
+
+    /// Still findable once the starting position has been replaced by its pipe
     pub fn get_starting_tile(&self) -> Option<&NetworkedTile> {
-        for row in &self.rows {
-            for tile in &row.0 {
-                if tile.tile == Tile::StartingPosition {
-                    return Some(tile);
+        self.get_tile(self.start?)
+    }
+
+    /// Replaces the starting position with the pipe it must be: the one shape, out of those joining
+    /// two neighbours that connect to it, which closes a loop.
+    pub fn resolve_start(&mut self) -> Result<Pipe, LoopError> {
+        let start = self.start.ok_or(LoopError::NoStart)?;
+        let start_tile = *self.get_tile(start).unwrap();
+        if let Tile::Pipe(pipe) = start_tile.tile {
+            return Ok(pipe);
+        }
+        let neighbors: Vec<Loc> = self
+            .get_tile_connections(&start_tile)
+            .iter()
+            .map(|t| t.location)
+            .collect();
+        if neighbors.len() < 2 {
+            return Err(LoopError::DisconnectedStart(start));
+        }
+        let mut candidates: Vec<Pipe> = vec![];
+        for (i, first) in neighbors.iter().enumerate() {
+            for second in &neighbors[i + 1..] {
+                if let Some(pipe) = Pipe::connecting(start, *first, *second) {
+                    candidates.push(pipe);
                 }
             }
         }
-        None
-    }
-
-    pub fn get_connected_paths(
-        &self,
-        this_tile: &NetworkedTile,
-        start_tile: &NetworkedTile,
-        prev_tile: Option<&NetworkedTile>,
-        mut existing_path: Path,
-    ) -> Path {
-        let connections = self.get_tile_connections(this_tile);
-        debug_assert!(connections.len() == 2);
-
-        let next_tile = match prev_tile {
-            Some(t) => match connections[0] == t {
-                true => connections[1],
-                false => connections[0],
-            },
-            None => {
-                existing_path.push(*this_tile);
-                connections[0]
+        let closing: Vec<Pipe> = candidates
+            .into_iter()
+            .filter(|pipe| {
+                self.set_tile(start, Tile::Pipe(*pipe));
+                self.get_loop().is_ok()
+            })
+            .collect();
+        match closing[..] {
+            [pipe] => {
+                self.set_tile(start, Tile::Pipe(pipe));
+                Ok(pipe)
             }
-        };
-        match next_tile == start_tile {
-            true => existing_path,
-            false => {
-                existing_path.push(*next_tile);
-                self.get_connected_paths(next_tile, start_tile, Some(this_tile), existing_path)
+            [] => {
+                self.set_tile(start, Tile::StartingPosition);
+                Err(LoopError::DisconnectedStart(start))
+            }
+            _ => {
+                self.set_tile(start, Tile::StartingPosition);
+                Err(LoopError::AmbiguousStart(start))
+            }
+        }
+    }
+
+    /// Walks the loop from the starting tile, which must already be resolved to a pipe
+    pub fn get_loop(&self) -> Result<Path, LoopError> {
+        let start_tile = self.get_starting_tile().ok_or(LoopError::NoStart)?;
+        let mut path = Path::default();
+        let mut prev_tile = None;
+        let mut this_tile = start_tile;
+        loop {
+            path.push(*this_tile);
+            let connections = self.get_tile_connections(this_tile);
+            let [first, second] = connections[..] else {
+                return Err(LoopError::BrokenLoop(this_tile.location));
+            };
+            let next_tile = match prev_tile {
+                Some(t) if first == t => second,
+                _ => first,
+            };
+            if next_tile == start_tile {
+                return Ok(path);
+            }
+            prev_tile = Some(this_tile);
+            this_tile = next_tile;
+        }
+    }
+
+    /// Scans each row of a resolved network, flipping between outside and inside whenever a loop tile connects north.
+    /// That way `F--J` and `L--7` count as a single crossing and `F--7` and `L--J` as none.
+    #[allow(dead_code)]
+    pub fn enclosed_tiles(&self, path: &Path) -> Vec<Loc> {
+        let loop_locations: HashSet<Loc> =
+            path.networked_tiles.iter().map(|t| t.location).collect();
+        let mut enclosed = vec![];
+        for row in &self.rows {
+            let mut inside = false;
+            for networked_tile in &row.0 {
+                if loop_locations.contains(&networked_tile.location) {
+                    if let Tile::Pipe(pipe) = networked_tile.tile {
+                        if pipe.connects_north() {
+                            inside = !inside;
+                        }
+                    }
+                } else if inside {
+                    enclosed.push(networked_tile.location);
+                }
             }
         }
+        enclosed
+    }
+
+    /// Draws the loop with box-drawing characters and marks enclosed tiles with `I`.
+    /// Everything else is shown as `.`, or with `ansi` the loop is bold and junk pipes are dimmed.
+    #[allow(dead_code)]
+    pub fn render(&self, path: &Path, ansi: bool) -> String {
+        let loop_locations: HashSet<Loc> =
+            path.networked_tiles.iter().map(|t| t.location).collect();
+        let enclosed: HashSet<Loc> = self.enclosed_tiles(path).into_iter().collect();
+        let mut out = String::new();
+        for row in &self.rows {
+            for networked_tile in &row.0 {
+                let c = networked_tile.tile.box_drawing();
+                let rendered = match (
+                    loop_locations.contains(&networked_tile.location),
+                    enclosed.contains(&networked_tile.location),
+                    ansi,
+                ) {
+                    (true, _, false) => c.to_string(),
+                    (true, _, true) => format!("\x1b[1m{}\x1b[0m", c),
+                    (false, true, false) => "I".to_string(),
+                    (false, true, true) => "\x1b[1;32mI\x1b[0m".to_string(),
+                    (false, false, false) => ".".to_string(),
+                    (false, false, true) => format!("\x1b[2m{}\x1b[0m", c),
+                };
+                out.push_str(&rendered);
+            }
+            out.push('\n');
+        }
+        out
     }
 
     fn get_tile_connections(&self, networked_tile: &NetworkedTile) -> Vec<&NetworkedTile> {
@@ -298,6 +465,7 @@ impl Network {
 mod tests {
     use super::*;
     use parser::parse_network;
+    use test_case::test_case;
 
     #[test]
     fn test_get_starting_tile() {
@@ -317,32 +485,108 @@ mod tests {
     }
 
     #[test]
-    fn get_connected_paths() {
+    fn get_loop() {
         let inp = include_str!("../../data/sample_input.txt");
-        let network = parse_network(inp).unwrap().1;
-        let starting_tile = network.get_tile(Loc::new(1, 1)).unwrap();
-        let actual =
-            network.get_connected_paths(starting_tile, starting_tile, None, Path::default());
+        let mut network = parse_network(inp).unwrap().1;
+        network.resolve_start().unwrap();
+        let actual = network.get_loop().unwrap();
         assert_eq!(actual.len(), 8);
     }
 
     #[test]
     fn get_furthest_from_starting() {
         let inp = include_str!("../../data/sample_input.txt");
-        let network = parse_network(inp).unwrap().1;
-        let starting_tile = network.get_tile(Loc::new(1, 1)).unwrap();
-        let connected_path =
-            network.get_connected_paths(starting_tile, starting_tile, None, Path::default());
+        let mut network = parse_network(inp).unwrap().1;
+        network.resolve_start().unwrap();
+        let connected_path = network.get_loop().unwrap();
         assert_eq!(connected_path.len() / 2, 4);
     }
 
     #[test]
     fn get_furthest_from_starting_sample_2() {
         let inp = include_str!("../../data/sample_input2.txt");
-        let network = parse_network(inp).unwrap().1;
-        let starting_tile = network.get_tile(Loc::new(1, 1)).unwrap();
-        let connected_path =
-            network.get_connected_paths(starting_tile, starting_tile, None, Path::default());
+        let mut network = parse_network(inp).unwrap().1;
+        network.resolve_start().unwrap();
+        let connected_path = network.get_loop().unwrap();
         assert_eq!(connected_path.len() / 2, 8);
     }
+
+    #[test_case(include_str!("../../data/sample_input.txt"), Ok(Pipe::SouthEastBend); "Sample 1")]
+    #[test_case(include_str!("../../data/sample_input2.txt"), Ok(Pipe::SouthEastBend); "Sample 2")]
+    #[test_case(include_str!("../../data/sample_input6.txt"), Ok(Pipe::SouthWestBend); "Sample 6")]
+    #[test_case("F-7\n|.|\nS-J\n|..\nL..\n", Ok(Pipe::NorthEastBend); "Three neighbours")]
+    #[test_case("F-7..\n|.|..\nL-S-7\n..|.|\n..L-J\n", Err(LoopError::AmbiguousStart(Loc::new(2, 2))); "Two loops")]
+    #[test_case("...\n.S-\n...\n", Err(LoopError::DisconnectedStart(Loc::new(1, 1))); "One neighbour")]
+    #[test_case("S-7\n|.|\n|.-\n", Err(LoopError::DisconnectedStart(Loc::new(0, 0))); "Open loop")]
+    #[test_case("F-7\n|.|\nL-J\n", Err(LoopError::NoStart); "No start")]
+    fn test_resolve_start(inp: &str, exp: Result<Pipe, LoopError>) {
+        let mut network = parse_network(inp).unwrap().1;
+        assert_eq!(network.resolve_start(), exp);
+    }
+
+    #[test_case(include_str!("../../data/sample_input.txt"), 1)]
+    #[test_case(include_str!("../../data/sample_input3.txt"), 4)]
+    #[test_case(include_str!("../../data/sample_input4.txt"), 4)]
+    #[test_case(include_str!("../../data/sample_input5.txt"), 8)]
+    #[test_case(include_str!("../../data/sample_input6.txt"), 10)]
+    fn test_enclosed_tiles(inp: &str, exp: usize) {
+        let mut network = parse_network(inp).unwrap().1;
+        network.resolve_start().unwrap();
+        let path = network.get_loop().unwrap();
+        assert_eq!(network.enclosed_tiles(&path).len(), exp);
+        assert_eq!(path.enclosed_count(), exp);
+    }
+
+    #[test]
+    fn test_enclosed_tile_locations() {
+        let inp = include_str!("../../data/sample_input3.txt");
+        let mut network = parse_network(inp).unwrap().1;
+        network.resolve_start().unwrap();
+        let path = network.get_loop().unwrap();
+        let expected = vec![
+            Loc::new(2, 6),
+            Loc::new(3, 6),
+            Loc::new(7, 6),
+            Loc::new(8, 6),
+        ];
+        assert_eq!(network.enclosed_tiles(&path), expected);
+    }
+
+    #[test]
+    fn test_render() {
+        let inp = include_str!("../../data/sample_input4.txt");
+        let mut network = parse_network(inp).unwrap().1;
+        network.resolve_start().unwrap();
+        let path = network.get_loop().unwrap();
+        let expected = "\
+..........
+.┌──────┐.
+.│┌────┐│.
+.││....││.
+.││....││.
+.│└─┐┌─┘│.
+.│II││II│.
+.└──┘└──┘.
+..........
+";
+        assert_eq!(network.render(&path, false), expected);
+    }
+
+    #[test]
+    fn test_render_ansi() {
+        let inp = "-L|F7\n7S-7|\nL|7||\n-L-J|\nL|-JF\n";
+        let mut network = parse_network(inp).unwrap().1;
+        network.resolve_start().unwrap();
+        let path = network.get_loop().unwrap();
+        let actual = network.render(&path, true);
+        let first_row = actual.lines().next().unwrap();
+        assert_eq!(
+            first_row,
+            "\x1b[2m─\x1b[0m\x1b[2m└\x1b[0m\x1b[2m│\x1b[0m\x1b[2m┌\x1b[0m\x1b[2m┐\x1b[0m"
+        );
+        let second_row = actual.lines().nth(1).unwrap();
+        assert!(second_row.starts_with("\x1b[2m┐\x1b[0m\x1b[1m┌\x1b[0m\x1b[1m─\x1b[0m"));
+        let third_row = actual.lines().nth(2).unwrap();
+        assert!(third_row.contains("\x1b[1;32mI\x1b[0m"));
+    }
 }
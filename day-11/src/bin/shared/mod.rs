@@ -56,13 +56,25 @@ impl NetworkedTile {
     fn new(tile: Tile, location: Loc) -> Self {
         Self { tile, location }
     }
-    pub fn calculate_distance(&self, other: &NetworkedTile) -> usize {
+    pub fn distance(&self, other: &NetworkedTile, metric: Metric) -> f64 {
         let x_diff = (self.location.x as isize - other.location.x as isize).unsigned_abs();
         let y_diff = (self.location.y as isize - other.location.y as isize).unsigned_abs();
-        x_diff + y_diff
+        match metric {
+            Metric::Manhattan => (x_diff + y_diff) as f64,
+            Metric::Chebyshev => x_diff.max(y_diff) as f64,
+            Metric::Euclidean => (x_diff as f64).hypot(y_diff as f64),
+        }
     }
 }
 
+#[allow(dead_code)]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Metric {
+    Manhattan,
+    Chebyshev,
+    Euclidean,
+}
+
 #[derive(Clone)]
 struct NetworkedRow(Vec<NetworkedTile>);
 
@@ -72,14 +84,21 @@ impl NetworkedRow {
             .0
             .iter()
             .enumerate()
-            .map(|(x, tile)| NetworkedTile::new(*tile, Loc::new(x as usize, row_num as usize)))
+            .map(|(x, tile)| NetworkedTile::new(*tile, Loc::new(x, row_num as usize)))
             .collect();
         Self(networked_tiles)
     }
 }
 
+/// Expansion never materialises rows or columns: galaxies keep their parsed locations and are
+/// shifted by the number of empty rows and columns before them when asked for.
 pub struct Universe {
     rows: Vec<NetworkedRow>,
+    galaxies: Vec<NetworkedTile>,
+    empty_rows_before: Vec<usize>,
+    empty_columns_before: Vec<usize>,
+    x_multiple: usize,
+    y_multiple: usize,
 }
 
 impl Universe {
@@ -87,81 +106,182 @@ impl Universe {
         let networked_rows: Vec<_> = rows
             .iter()
             .enumerate()
-            .map(|(row_num, naive_row)| (NetworkedRow::new(row_num as u8, naive_row.clone())))
+            .map(|(row_num, naive_row)| NetworkedRow::new(row_num as u8, naive_row.clone()))
             .collect();
-        Self {
+        let galaxies = networked_rows
+            .iter()
+            .flat_map(|row| row.0.iter().filter(|nt| nt.tile == Tile::Galaxy).copied())
+            .collect();
+        let mut universe = Self {
             rows: networked_rows,
-        }
+            galaxies,
+            empty_rows_before: vec![],
+            empty_columns_before: vec![],
+            x_multiple: 1,
+            y_multiple: 1,
+        };
+        universe.empty_rows_before =
+            prefix_counts(&universe.get_rows_to_expand(), universe.height());
+        universe.empty_columns_before =
+            prefix_counts(&universe.get_columns_to_expand(), universe.width());
+        universe
+    }
+
+    fn width(&self) -> usize {
+        self.rows.first().map_or(0, |row| row.0.len())
     }
 
+    fn height(&self) -> usize {
+        self.rows.len()
+    }
+
+    #[cfg(test)]
     fn get_tile(&self, loc: Loc) -> Option<&NetworkedTile> {
-        self.rows.get(loc.y as usize)?.0.get(loc.x as usize)
+        self.rows.get(loc.y)?.0.get(loc.x)
     }
 
-    pub fn get_all_galaxies(&self) -> Vec<&NetworkedTile> {
-        self.rows.iter().flat_map(|row| {
-            row.0.iter().filter(|nt| nt.tile == Tile::Galaxy).collect::<Vec<_>>()
-        }).collect()
+    /// Galaxies at their expanded locations
+    pub fn get_all_galaxies(&self) -> Vec<NetworkedTile> {
+        self.galaxies
+            .iter()
+            .map(|galaxy| {
+                let Loc { x, y } = galaxy.location;
+                let expanded = Loc::new(
+                    x + self.empty_columns_before[x] * (self.x_multiple - 1),
+                    y + self.empty_rows_before[y] * (self.y_multiple - 1),
+                );
+                NetworkedTile::new(galaxy.tile, expanded)
+            })
+            .collect()
     }
 
+    /// Every empty row and column becomes `multiple` of itself, replacing any earlier expansion
     pub fn expand(&mut self, multiple: usize) {
-        let cols_to_expand = self.get_columns_to_expand();
-        let rows_to_expand = self.get_rows_to_expand();
-        println!("Expanding all rows");
-        rows_to_expand.iter().rev().for_each(|i| {
-            for _ in 0..multiple - 1 {
-                self.rows.insert(*i, self.rows[*i].clone())
+        self.expand_axes(multiple, multiple);
+    }
+
+    pub fn expand_axes(&mut self, x_multiple: usize, y_multiple: usize) {
+        self.x_multiple = x_multiple;
+        self.y_multiple = y_multiple;
+    }
+
+    /// Width and height after expansion
+    #[allow(dead_code)]
+    pub fn dimensions(&self) -> (usize, usize) {
+        (
+            self.width() + self.empty_columns_before[self.width()] * (self.x_multiple - 1),
+            self.height() + self.empty_rows_before[self.height()] * (self.y_multiple - 1),
+        )
+    }
+
+    /// Sum of Manhattan distances between every pair of galaxies, without visiting the pairs
+    pub fn total_distance(&self) -> usize {
+        let galaxies = self.get_all_galaxies();
+        let xs = galaxies.iter().map(|g| g.location.x as isize).collect();
+        let ys = galaxies.iter().map(|g| g.location.y as isize).collect();
+        (sum_of_differences(xs) + sum_of_differences(ys)) as usize
+    }
+
+    /// Manhattan and Chebyshev sums are O(n log n); Euclidean has no such shortcut and walks
+    /// every pair
+    #[allow(dead_code)]
+    pub fn total_distance_with(&self, metric: Metric) -> f64 {
+        match metric {
+            Metric::Manhattan => self.total_distance() as f64,
+            // Rotating by 45 degrees turns Chebyshev distance into half a Manhattan distance
+            Metric::Chebyshev => {
+                let galaxies = self.get_all_galaxies();
+                let us = galaxies
+                    .iter()
+                    .map(|g| (g.location.x + g.location.y) as isize)
+                    .collect();
+                let vs = galaxies
+                    .iter()
+                    .map(|g| g.location.x as isize - g.location.y as isize)
+                    .collect();
+                ((sum_of_differences(us) + sum_of_differences(vs)) / 2) as f64
             }
-        });
-        println!("Transposing Rows");
-        self.rows = self.transposed_rows();
-        println!("Expanding all columns");
-        cols_to_expand.iter().rev().for_each(|i| {
-            for _ in 0..multiple - 1 {
-                self.rows.insert(*i, self.rows[*i].clone())
+            Metric::Euclidean => {
+                let galaxies = self.get_all_galaxies();
+                pairs(&galaxies).map(|(a, b)| a.distance(b, metric)).sum()
             }
-        });
+        }
+    }
+
+    /// The first pair at the smallest distance, or None with fewer than two galaxies
+    #[allow(dead_code)]
+    pub fn nearest_pair(&self, metric: Metric) -> Option<(NetworkedTile, NetworkedTile)> {
+        let galaxies = self.get_all_galaxies();
+        pairs(&galaxies)
+            .min_by(|(a1, b1), (a2, b2)| {
+                a1.distance(b1, metric).total_cmp(&a2.distance(b2, metric))
+            })
+            .map(|(a, b)| (*a, *b))
+    }
 
-        println!("Transposing Rows");
-        self.rows = self.transposed_rows();
-        println!("Recalculating");
-        self.recalculate_loc();
+    /// The last pair at the largest distance, or None with fewer than two galaxies
+    #[allow(dead_code)]
+    pub fn farthest_pair(&self, metric: Metric) -> Option<(NetworkedTile, NetworkedTile)> {
+        let galaxies = self.get_all_galaxies();
+        pairs(&galaxies)
+            .max_by(|(a1, b1), (a2, b2)| {
+                a1.distance(b1, metric).total_cmp(&a2.distance(b2, metric))
+            })
+            .map(|(a, b)| (*a, *b))
     }
 
     fn get_columns_to_expand(&self) -> Vec<usize> {
-        self.determine_expand_points(&self.transposed_rows())
+        (0..self.width())
+            .filter(|x| self.rows.iter().all(|row| row.0[*x].tile == Tile::Space))
+            .collect()
     }
 
     fn get_rows_to_expand(&self) -> Vec<usize> {
-        self.determine_expand_points(&self.rows)
-    }
-    fn determine_expand_points(&self, rows: &Vec<NetworkedRow>) -> Vec<usize> {
-        rows.iter()
+        self.rows
+            .iter()
             .enumerate()
             .filter(|(_, row)| row.0.iter().all(|nt| nt.tile == Tile::Space))
             .map(|(i, _)| i)
             .collect()
     }
-    fn recalculate_loc(&mut self) {
-        for (row_num, row) in self.rows.iter_mut().enumerate() {
-            for (col_num, tile) in row.0.iter_mut().enumerate() {
-                tile.location.x = col_num;
-                tile.location.y = row_num;
-            }
+}
+
+/// `counts[i]` is how many of the sorted `points` are below `i`, for every `i` up to and including `len`
+fn prefix_counts(points: &[usize], len: usize) -> Vec<usize> {
+    let mut counts = Vec::with_capacity(len + 1);
+    let mut points = points.iter().peekable();
+    let mut count = 0;
+    for i in 0..=len {
+        while points.next_if(|p| **p < i).is_some() {
+            count += 1;
         }
+        counts.push(count);
     }
+    counts
+}
 
-    fn transposed_rows(&self) -> Vec<NetworkedRow> {
-        (0..self.rows[0].0.len())
-            .map(|i| {
-                self.rows
-                    .iter()
-                    .map(|inner| inner.0[i])
-                    .collect::<Vec<NetworkedTile>>()
-            })
-            .map(NetworkedRow)
-            .collect()
-    }
+/// Sum of `|a - b|` over every unordered pair: once sorted, each value is subtracted from all
+/// the values after it
+fn sum_of_differences(mut values: Vec<isize>) -> isize {
+    values.sort_unstable();
+    let mut prefix = 0;
+    values
+        .iter()
+        .enumerate()
+        .map(|(i, value)| {
+            let total = value * i as isize - prefix;
+            prefix += value;
+            total
+        })
+        .sum()
+}
+
+/// Every unordered pair, lazily
+fn pairs(galaxies: &[NetworkedTile]) -> impl Iterator<Item = (&NetworkedTile, &NetworkedTile)> {
+    galaxies
+        .iter()
+        .enumerate()
+        .flat_map(move |(i, a)| galaxies[i + 1..].iter().map(move |b| (a, b)))
 }
 
 #[cfg(test)]
@@ -191,27 +311,30 @@ mod tests {
     fn test_expand() {
         let inp = include_str!("../../data/sample_input.txt");
         let mut universe = parse_universe(inp).unwrap().1;
-        assert_eq!(universe.rows.len(), 10);
-        assert_eq!(universe.rows[0].0.len(), 10);
+        assert_eq!(universe.dimensions(), (10, 10));
         assert_eq!(
             universe.get_tile(Loc::new(3, 0)).unwrap().tile,
             Tile::Galaxy
         );
-        assert_eq!(
-            universe.get_tile(Loc::new(3, 0)).unwrap().location,
-            Loc::new(3, 0)
-        );
+        assert_eq!(universe.get_all_galaxies()[0].location, Loc::new(3, 0));
         universe.expand(2);
-        assert_eq!(universe.rows.len(), 12);
-        assert_eq!(universe.rows[0].0.len(), 13);
-        assert_eq!(
-            universe.get_tile(Loc::new(4, 0)).unwrap().tile,
-            Tile::Galaxy
-        );
-        assert_eq!(
-            universe.get_tile(Loc::new(4, 0)).unwrap().location,
-            Loc::new(4, 0)
-        );
+        assert_eq!(universe.dimensions(), (13, 12));
+        assert_eq!(universe.get_all_galaxies()[0].location, Loc::new(4, 0));
+    }
+
+    #[test_case((2, 1), (13, 10), Loc::new(8, 4); "Columns only")]
+    #[test_case((1, 3), (10, 14), Loc::new(6, 6); "Rows only")]
+    #[test_case((1_000_000, 1_000_000), (3_000_007, 2_000_008), Loc::new(2_000_004, 1_000_003); "Million")]
+    fn test_expand_axes(
+        (x_multiple, y_multiple): (usize, usize),
+        exp_dimensions: (usize, usize),
+        exp_loc: Loc,
+    ) {
+        let inp = include_str!("../../data/sample_input.txt");
+        let mut universe = parse_universe(inp).unwrap().1;
+        universe.expand_axes(x_multiple, y_multiple);
+        assert_eq!(universe.dimensions(), exp_dimensions);
+        assert_eq!(universe.get_all_galaxies()[3].location, exp_loc);
     }
 
     #[test_case((Loc::new(1, 6), Loc::new(5, 11)), 9)]
@@ -223,12 +346,18 @@ mod tests {
         let inp = include_str!("../../data/sample_input.txt");
         let mut universe = parse_universe(inp).unwrap().1;
         universe.expand(2);
-        let galaxy_1 = universe.get_tile(loc1).unwrap();
-        let galaxy_2 = universe.get_tile(loc2).unwrap();
+        let galaxies = universe.get_all_galaxies();
+        let galaxy_1 = galaxies.iter().find(|g| g.location == loc1).unwrap();
+        let galaxy_2 = galaxies.iter().find(|g| g.location == loc2).unwrap();
         assert_eq!(galaxy_1.tile, Tile::Galaxy);
         assert_eq!(galaxy_2.tile, Tile::Galaxy);
-        let actual = galaxy_1.calculate_distance(galaxy_2);
-        assert_eq!(actual, exp);
+        let actual = galaxy_1.distance(galaxy_2, Metric::Manhattan);
+        assert_eq!(actual, exp as f64);
+    }
+
+    #[test]
+    fn test_prefix_counts() {
+        assert_eq!(prefix_counts(&[1, 2, 5], 6), vec![0, 0, 1, 2, 2, 2, 3]);
     }
 
     #[test]
@@ -238,4 +367,48 @@ mod tests {
         let actual = universe.get_all_galaxies();
         assert_eq!(actual.len(), 9);
     }
+
+    #[test_case(2, 374)]
+    #[test_case(10, 1030)]
+    #[test_case(100, 8410)]
+    fn test_total_distance(multiple: usize, exp: usize) {
+        let inp = include_str!("../../data/sample_input.txt");
+        let mut universe = parse_universe(inp).unwrap().1;
+        universe.expand(multiple);
+        assert_eq!(universe.total_distance(), exp);
+    }
+
+    #[test_case(Metric::Manhattan)]
+    #[test_case(Metric::Chebyshev)]
+    #[test_case(Metric::Euclidean)]
+    fn test_total_distance_with(metric: Metric) {
+        let inp = include_str!("../../data/sample_input.txt");
+        let mut universe = parse_universe(inp).unwrap().1;
+        universe.expand_axes(2, 3);
+        let galaxies = universe.get_all_galaxies();
+        let mut exp = 0.0;
+        for (i, a) in galaxies.iter().enumerate() {
+            for b in &galaxies[i + 1..] {
+                exp += a.distance(b, metric);
+            }
+        }
+        assert!((universe.total_distance_with(metric) - exp).abs() < 1e-9);
+    }
+
+    #[test_case(Metric::Manhattan, (Loc::new(9, 1), Loc::new(8, 5)), (Loc::new(9, 1), Loc::new(0, 11)))]
+    #[test_case(Metric::Chebyshev, (Loc::new(12, 7), Loc::new(9, 10)), (Loc::new(12, 7), Loc::new(0, 11)))]
+    #[test_case(Metric::Euclidean, (Loc::new(9, 1), Loc::new(8, 5)), (Loc::new(9, 1), Loc::new(0, 11)))]
+    fn test_nearest_and_farthest_pair(
+        metric: Metric,
+        exp_nearest: (Loc, Loc),
+        exp_farthest: (Loc, Loc),
+    ) {
+        let inp = include_str!("../../data/sample_input.txt");
+        let mut universe = parse_universe(inp).unwrap().1;
+        universe.expand(2);
+        let (a, b) = universe.nearest_pair(metric).unwrap();
+        assert_eq!((a.location, b.location), exp_nearest);
+        let (a, b) = universe.farthest_pair(metric).unwrap();
+        assert_eq!((a.location, b.location), exp_farthest);
+    }
 }
@@ -1,5 +1,6 @@
 mod shared;
-use shared::{parser::parse_collection_group, CollectionGroup};
+use aoc_utils::parse_collection_group;
+use shared::{summarize, Valley};
 
 fn main() {
     let inp = include_str!("../data/puzzle_input.txt");
@@ -8,14 +9,12 @@ fn main() {
     println!("{}", out);
 }
 
-fn summarize_notes(collection_group: CollectionGroup) -> usize {
-    let mut sum = 0;
-    for collection in collection_group {
-        let row_score = collection.find_row_symmetry().map_or(0, |r| r * 100);
-        let col_score = collection.find_column_symmetry().unwrap_or(0);
-        sum += row_score + col_score;
-    }
-    sum
+fn summarize_notes(collection_group: Vec<Valley>) -> usize {
+    collection_group
+        .iter()
+        .filter_map(|collection| collection.find_reflection(0))
+        .map(|reflection| summarize(&reflection))
+        .sum()
 }
 
 #[cfg(test)]
@@ -1,5 +1,33 @@
 mod shared;
+use aoc_utils::parse_collection_group;
+use shared::{summarize, Valley};
 
 fn main() {
+    let inp = include_str!("../data/puzzle_input.txt");
+    let collection_group = parse_collection_group(inp).unwrap().1;
+    let out = summarize_smudged_notes(collection_group);
+    println!("{}", out);
+}
+
+fn summarize_smudged_notes(collection_group: Vec<Valley>) -> usize {
+    collection_group
+        .iter()
+        .filter_map(|collection| collection.find_reflection(1))
+        .map(|reflection| summarize(&reflection))
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
 
+    #[test]
+    fn test_summarize_smudged_notes() {
+        let inp = include_str!("../data/sample_input.txt");
+        let collection_group = parse_collection_group(inp).unwrap().1;
+        let actual = summarize_smudged_notes(collection_group);
+        let expected = 400;
+        assert_eq!(actual, expected);
+    }
 }
@@ -0,0 +1,43 @@
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use aoc_utils::{parse_collection, Collection};
+
+use crate::shared::packed::PackedField;
+use crate::shared::{ParabolicFieldCollection, ParabolicFieldTile};
+mod shared;
+
+/// Compares spinning the puzzle input with the tile collection against the bit-packed field.
+/// Run with `cargo run --release --bin bench`.
+fn main() {
+    let inp = include_str!("../data/puzzle_input.txt");
+    let col: Collection<ParabolicFieldTile> = parse_collection(inp).unwrap().1;
+    let spins = 20;
+
+    let collection_time = time(|| {
+        let mut field = ParabolicFieldCollection(col.clone());
+        for _ in 0..spins {
+            field = field.spin();
+        }
+        field.north_load()
+    });
+    let packed_time = time(|| {
+        let mut field = PackedField::try_from(&col).unwrap();
+        for _ in 0..spins {
+            field.spin();
+        }
+        field.north_load()
+    });
+    println!("{} spins with Collection:  {:?}", spins, collection_time);
+    println!("{} spins with PackedField: {:?}", spins, packed_time);
+    println!(
+        "speedup: {:.0}x",
+        collection_time.as_secs_f64() / packed_time.as_secs_f64()
+    );
+}
+
+fn time<F: Fn() -> usize>(f: F) -> Duration {
+    let start = Instant::now();
+    black_box(f());
+    start.elapsed()
+}
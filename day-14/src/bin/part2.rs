@@ -1,32 +1,28 @@
 use aoc_utils::{parse_collection, Collection};
 
-use crate::shared::{ParabolicFieldCollection, ParabolicFieldTile};
+use crate::shared::packed::PackedField;
+use crate::shared::ParabolicFieldTile;
 mod shared;
 
-fn main() {}
-
-fn load_after_cycles(collection: ParabolicFieldCollection, count: usize) -> usize {
-    64
+fn main() {
+    let inp = include_str!("../data/puzzle_input.txt");
+    let col: Collection<ParabolicFieldTile> = parse_collection(inp).unwrap().1;
+    let field = PackedField::try_from(&col).unwrap();
+    println!("{}", load_after_cycles(&field, 1_000_000_000));
 }
 
-fn calcuate_north_load(parabolic_field_collection: Vec<Vec<ParabolicFieldTile>>) -> usize {
-    let mut sum = 0;
-    for col in parabolic_field_collection {
-        for (row_num, tile) in col.iter().rev().enumerate() {
-            if tile == &ParabolicFieldTile::Round {
-                sum += row_num + 1;
-            }
-        }
-    }
-    sum
+fn load_after_cycles(field: &PackedField, count: usize) -> usize {
+    field
+        .north_load_after_spins(count)
+        .expect("the field should settle into a repeating pattern")
 }
 
 #[test]
 fn test_parse_collection() {
     let inp = include_str!("../data/sample_input.txt");
     let col: Collection<ParabolicFieldTile> = parse_collection(inp).unwrap().1;
-    let col = ParabolicFieldCollection(col);
-    let actual = load_after_cycles(col, 64);
+    let field = PackedField::try_from(&col).unwrap();
+    let actual = load_after_cycles(&field, 1_000_000_000);
 
     assert_eq!(actual, 64);
 }
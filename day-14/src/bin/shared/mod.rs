@@ -1,7 +1,50 @@
-use aoc_utils::{BadTileTypeError, Collection, Column, ParseableCharacters, Row, Tile};
-use itertools::Itertools;
+pub mod packed;
 
-#[derive(Debug, PartialEq, Copy, Clone)]
+use aoc_utils::cycle::detect_cycle;
+use aoc_utils::{BadTileTypeError, Collection, Column, Direction, ParseableCharacters};
+use std::fmt::Display;
+use std::hash::Hash;
+use std::str::FromStr;
+
+/// Spins to try before giving up on finding a repeated field
+const MAX_SPINS: usize = 10_000;
+
+/// Applies `run` to `start` `times` times. Once the states seen repeat, only the runs up to the
+/// equivalent step before the repeat are simulated again. None only if `times` is past
+/// `MAX_SPINS` and no repeat shows up within `MAX_SPINS` runs.
+fn run_repeatedly<T, S, K, R>(start: &T, times: usize, state: K, run: R) -> Option<T>
+where
+    T: Clone,
+    S: Hash + Eq,
+    K: Fn(&T) -> S,
+    R: Fn(&T) -> T,
+{
+    let mut current = start.clone();
+    let mut runs = 0;
+    let cycle = detect_cycle(
+        || {
+            let key = state(&current);
+            if runs < times {
+                current = run(&current);
+                runs += 1;
+            }
+            key
+        },
+        times.saturating_add(1).min(MAX_SPINS),
+    );
+    match cycle {
+        Some(cycle) => {
+            let mut current = start.clone();
+            for _ in 0..cycle.equivalent_step(times) {
+                current = run(&current);
+            }
+            Some(current)
+        }
+        None => (runs == times).then_some(current),
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
 pub enum ParabolicFieldTile {
     Round,
     Cube,
@@ -19,68 +62,193 @@ impl TryFrom<char> for ParabolicFieldTile {
         }
     }
 }
+impl Display for ParabolicFieldTile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            ParabolicFieldTile::Round => "O",
+            ParabolicFieldTile::Cube => "#",
+            ParabolicFieldTile::Empty => ".",
+        })
+    }
+}
 impl ParseableCharacters for ParabolicFieldTile {
     fn valid_chars() -> Vec<char> {
         vec!['O', '#', '.']
     }
 }
 
-enum Direction {
+/// Along a row or column: towards its higher or lower index
+enum LineDirection {
     Positive,
     Negative,
 }
 
+#[derive(Debug, PartialEq)]
+pub struct BadTiltError(pub char);
+
+/// A sequence of tilts written as compass letters, e.g. `NWSE` for one spin cycle
+#[derive(Debug, PartialEq, Clone)]
+pub struct TiltProgram(Vec<Direction>);
+
+impl TiltProgram {
+    pub fn spin() -> Self {
+        Self(vec![
+            Direction::North,
+            Direction::West,
+            Direction::South,
+            Direction::East,
+        ])
+    }
+}
+
+impl FromStr for TiltProgram {
+    type Err = BadTiltError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.trim()
+            .chars()
+            .map(|c| match c {
+                'N' => Ok(Direction::North),
+                'E' => Ok(Direction::East),
+                'S' => Ok(Direction::South),
+                'W' => Ok(Direction::West),
+                _ => Err(BadTiltError(c)),
+            })
+            .collect::<Result<_, _>>()
+            .map(Self)
+    }
+}
+
+#[derive(Clone)]
 pub struct ParabolicFieldCollection(pub Collection<ParabolicFieldTile>);
+// Part 2 only spins the packed field
+#[allow(dead_code)]
 impl ParabolicFieldCollection {
-    pub fn tilt_north(&self) -> Vec<Vec<ParabolicFieldTile>> {
-        let mut tilted = vec![];
-        for i in 0..self.0.count_columns() {
-            tilted.push(ParabolicFieldCollection::tilt(
-                self.0.get_column(i),
-                Direction::Negative,
-            ));
-        }
-        tilted
-    }
-    pub fn tilt_south(&self) -> Vec<Vec<ParabolicFieldTile>> {
-        let mut tilted = vec![];
-        for i in 0..self.0.count_columns() {
-            tilted.push(ParabolicFieldCollection::tilt(
-                self.0.get_column(i),
-                Direction::Positive,
-            ));
-        }
-        tilted
-    }
-    pub fn tilt_west(&self) -> Vec<Vec<ParabolicFieldTile>> {
-        let mut tilted = vec![];
-        for i in 0..self.0.count_rows() {
-            tilted.push(ParabolicFieldCollection::tilt(
-                self.0.get_row(i),
-                Direction::Negative,
-            ));
-        }
-        tilted
-    }
-    pub fn tilt_east(&self) -> Vec<Vec<ParabolicFieldTile>> {
-        let mut tilted = vec![];
-        for i in 0..self.0.count_rows() {
-            tilted.push(ParabolicFieldCollection::tilt(
-                self.0.get_row(i),
-                Direction::Positive,
-            ));
+    pub fn tilt(&self, direction: Direction) -> Self {
+        match direction {
+            Direction::North => self.tilt_north(),
+            Direction::East => self.tilt_east(),
+            Direction::South => self.tilt_south(),
+            Direction::West => self.tilt_west(),
         }
-        tilted
+    }
+    pub fn tilt_north(&self) -> Self {
+        let columns = (0..self.0.count_columns() as isize)
+            .map(|i| {
+                ParabolicFieldCollection::tilt_line(self.0.get_column(i), LineDirection::Negative)
+            })
+            .collect();
+        Self::from_columns(columns)
+    }
+    pub fn tilt_south(&self) -> Self {
+        let columns = (0..self.0.count_columns() as isize)
+            .map(|i| {
+                ParabolicFieldCollection::tilt_line(self.0.get_column(i), LineDirection::Positive)
+            })
+            .collect();
+        Self::from_columns(columns)
+    }
+    pub fn tilt_west(&self) -> Self {
+        let rows = (0..self.0.count_rows() as isize)
+            .map(|i| {
+                ParabolicFieldCollection::tilt_line(self.0.get_row(i), LineDirection::Negative)
+            })
+            .collect();
+        Self(Collection::from_rows(rows))
+    }
+    pub fn tilt_east(&self) -> Self {
+        let rows = (0..self.0.count_rows() as isize)
+            .map(|i| {
+                ParabolicFieldCollection::tilt_line(self.0.get_row(i), LineDirection::Positive)
+            })
+            .collect();
+        Self(Collection::from_rows(rows))
+    }
+
+    /// One cycle: tilt north, then west, then south, then east
+    pub fn spin(&self) -> Self {
+        self.run(&TiltProgram::spin())
+    }
+
+    pub fn run(&self, program: &TiltProgram) -> Self {
+        program
+            .0
+            .iter()
+            .fold(Self(self.0.clone()), |field, direction| {
+                field.tilt(*direction)
+            })
+    }
+
+    /// Skips ahead once the field starts repeating; None if `times` is past `MAX_SPINS` and
+    /// no repeat shows up within them
+    pub fn run_times(&self, program: &TiltProgram, times: usize) -> Option<Self> {
+        run_repeatedly(
+            self,
+            times,
+            |field| field.rows(),
+            |field| field.run(program),
+        )
+    }
+
+    /// Each round rock adds the number of rows from it to the south edge, inclusive
+    pub fn north_load(&self) -> usize {
+        self.load(Direction::North)
     }
 
-    fn tilt(
+    /// Each round rock adds its distance from the opposite wall, counting its own tile
+    pub fn load(&self, wall: Direction) -> usize {
+        let row_count = self.0.count_rows();
+        let column_count = self.0.count_columns();
+        let rows = self.rows();
+        let rounds = rows.iter().enumerate().flat_map(|(y, row)| {
+            row.iter()
+                .enumerate()
+                .filter(|(_, t)| **t == ParabolicFieldTile::Round)
+                .map(move |(x, _)| (x, y))
+        });
+        rounds
+            .map(|(x, y)| match wall {
+                Direction::North => row_count - y,
+                Direction::East => x + 1,
+                Direction::South => y + 1,
+                Direction::West => column_count - x,
+            })
+            .sum()
+    }
+
+    pub fn north_load_after_spins(&self, spins: usize) -> Option<usize> {
+        self.run_times(&TiltProgram::spin(), spins)
+            .map(|field| field.north_load())
+    }
+
+    fn rows(&self) -> Vec<Vec<ParabolicFieldTile>> {
+        (0..self.0.count_rows() as isize)
+            .map(|i| {
+                self.0
+                    .get_row(i)
+                    .into_iter()
+                    .map(|t| *t.tile_type())
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn from_columns(columns: Vec<Vec<ParabolicFieldTile>>) -> Self {
+        let row_count = columns.first().map_or(0, |c| c.len());
+        let rows = (0..row_count)
+            .map(|y| columns.iter().map(|column| column[y]).collect())
+            .collect();
+        Self(Collection::from_rows(rows))
+    }
+
+    fn tilt_line(
         row_or_col: Column<ParabolicFieldTile>,
-        direction: Direction,
+        direction: LineDirection,
     ) -> Vec<ParabolicFieldTile> {
         let mut data_grouped: Vec<ParabolicFieldTile> = vec![];
         let mut empties = 0;
         match direction {
-            Direction::Positive => {
+            LineDirection::Positive => {
                 for tile in row_or_col.into_iter().rev() {
                     match tile.tile_type() {
                         ParabolicFieldTile::Round => data_grouped.push(ParabolicFieldTile::Round),
@@ -94,8 +262,8 @@ impl ParabolicFieldCollection {
                 }
                 data_grouped.extend(vec![ParabolicFieldTile::Empty; empties]);
                 data_grouped.reverse();
-            },
-            Direction::Negative => {
+            }
+            LineDirection::Negative => {
                 for tile in row_or_col {
                     match tile.tile_type() {
                         ParabolicFieldTile::Round => data_grouped.push(ParabolicFieldTile::Round),
@@ -114,6 +282,15 @@ impl ParabolicFieldCollection {
     }
 }
 
+impl Display for ParabolicFieldCollection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for i in 0..self.0.count_rows() as isize {
+            writeln!(f, "{}", self.0.get_row(i))?;
+        }
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -140,7 +317,8 @@ mod tests {
     fn test_tilt_row_negative() {
         let inp = include_str!("../../data/sample_input.txt");
         let collection: Collection<ParabolicFieldTile> = parse_collection(inp).unwrap().1;
-        let actual = ParabolicFieldCollection::tilt(collection.get_column(2), Direction::Negative);
+        let actual =
+            ParabolicFieldCollection::tilt_line(collection.get_column(2), LineDirection::Negative);
         let expected = vec![
             ParabolicFieldTile::Round,
             ParabolicFieldTile::Empty,
@@ -160,7 +338,8 @@ mod tests {
     fn test_tilt_row_positive() {
         let inp = include_str!("../../data/sample_input.txt");
         let collection: Collection<ParabolicFieldTile> = parse_collection(inp).unwrap().1;
-        let actual = ParabolicFieldCollection::tilt(collection.get_column(2), Direction::Positive);
+        let actual =
+            ParabolicFieldCollection::tilt_line(collection.get_column(2), LineDirection::Positive);
         let expected = vec![
             ParabolicFieldTile::Empty,
             ParabolicFieldTile::Empty,
@@ -175,4 +354,97 @@ mod tests {
         ];
         assert_eq!(actual, expected);
     }
+
+    #[test]
+    fn test_tilt_north() {
+        let inp = include_str!("../../data/sample_input.txt");
+        let col = ParabolicFieldCollection(parse_collection(inp).unwrap().1);
+        let expected = ParabolicFieldCollection(
+            parse_collection(include_str!("../../data/sample_tilted_north.txt"))
+                .unwrap()
+                .1,
+        );
+        assert_eq!(col.tilt_north().0, expected.0);
+        assert_eq!(col.tilt_north().north_load(), 136);
+    }
+
+    #[test_case(1, include_str!("../../data/sample_spin_1.txt"))]
+    #[test_case(2, include_str!("../../data/sample_spin_2.txt"))]
+    #[test_case(3, include_str!("../../data/sample_spin_3.txt"))]
+    fn test_spin(count: usize, expected: &str) {
+        let inp = include_str!("../../data/sample_input.txt");
+        let mut col = ParabolicFieldCollection(parse_collection(inp).unwrap().1);
+        for _ in 0..count {
+            col = col.spin();
+        }
+        assert_eq!(col.to_string(), expected);
+    }
+
+    #[test]
+    fn test_display_round_trips() {
+        let inp = include_str!("../../data/sample_input.txt");
+        let col = ParabolicFieldCollection(parse_collection(inp).unwrap().1);
+        assert_eq!(col.to_string(), inp);
+    }
+
+    #[test_case("N", 1, include_str!("../../data/sample_tilted_north.txt"); "North")]
+    #[test_case("NWSE", 3, include_str!("../../data/sample_spin_3.txt"); "Three spins")]
+    #[test_case("NNWWSSEE", 3, include_str!("../../data/sample_spin_3.txt"); "Repeated tilts are idempotent")]
+    fn test_run_times(program: &str, times: usize, expected: &str) {
+        let inp = include_str!("../../data/sample_input.txt");
+        let col = ParabolicFieldCollection(parse_collection(inp).unwrap().1);
+        let program: TiltProgram = program.parse().unwrap();
+        let actual = col.run_times(&program, times).unwrap();
+        assert_eq!(actual.to_string(), expected);
+    }
+
+    #[test]
+    fn test_program_of_two_spins() {
+        let inp = include_str!("../../data/sample_input.txt");
+        let col = ParabolicFieldCollection(parse_collection(inp).unwrap().1);
+        let double_spin: TiltProgram = "NWSENWSE".parse().unwrap();
+        let actual = col.run_times(&double_spin, 500_000_000).unwrap();
+        let expected = col.run_times(&TiltProgram::spin(), 1_000_000_000).unwrap();
+        assert_eq!(actual.to_string(), expected.to_string());
+        assert_eq!(actual.load(Direction::North), 64);
+    }
+
+    #[test]
+    fn test_bad_tilt_program() {
+        assert_eq!("NWX".parse::<TiltProgram>(), Err(BadTiltError('X')));
+    }
+
+    #[test_case(Direction::North, 3)]
+    #[test_case(Direction::East, 3)]
+    #[test_case(Direction::South, 3)]
+    #[test_case(Direction::West, 5)]
+    fn test_load(wall: Direction, exp: usize) {
+        let col = ParabolicFieldCollection(parse_collection("O..\n.O.\n").unwrap().1);
+        assert_eq!(col.load(wall), exp);
+    }
+
+    #[test_case(0, 104)]
+    #[test_case(1, 87)]
+    #[test_case(1_000_000_000, 64)]
+    fn test_north_load_after_spins(spins: usize, exp: usize) {
+        let inp = include_str!("../../data/sample_input.txt");
+        let col = ParabolicFieldCollection(parse_collection(inp).unwrap().1);
+        assert_eq!(col.north_load_after_spins(spins), Some(exp));
+    }
+
+    #[test_case(3, Some(3); "Few runs without a repeat")]
+    #[test_case(MAX_SPINS, Some(MAX_SPINS); "Exactly the limit")]
+    #[test_case(MAX_SPINS + 1, None; "Past the limit without a repeat")]
+    fn test_run_repeatedly_without_cycle(times: usize, exp: Option<usize>) {
+        assert_eq!(run_repeatedly(&0, times, |n| *n, |n| n + 1), exp);
+    }
+
+    #[test_case(2, 2)]
+    #[test_case(1_000_000_001, 1)]
+    fn test_run_repeatedly_with_cycle(times: usize, exp: usize) {
+        assert_eq!(
+            run_repeatedly(&0, times, |n| *n, |n| (n + 1) % 4),
+            Some(exp)
+        );
+    }
 }
@@ -0,0 +1,246 @@
+use aoc_utils::{Collection, Direction};
+
+use super::{run_repeatedly, ParabolicFieldTile};
+
+/// Widest field a `PackedField` row can hold
+pub const MAX_WIDTH: usize = u128::BITS as usize;
+
+#[derive(Debug, PartialEq)]
+pub struct FieldTooWideError(pub usize);
+
+/// The field as one bitmask of round rocks and one of cube rocks per row, bit `x` for column `x`.
+/// Every tilt moves whole rows of rocks at once with shifts and masks.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PackedField {
+    width: usize,
+    round: Vec<u128>,
+    cube: Vec<u128>,
+}
+
+impl PackedField {
+    pub fn tilt(&mut self, direction: Direction) {
+        match direction {
+            Direction::North => self.tilt_vertically(true),
+            Direction::South => self.tilt_vertically(false),
+            Direction::West => self.tilt_horizontally(true),
+            Direction::East => self.tilt_horizontally(false),
+        }
+    }
+
+    /// One cycle: tilt north, then west, then south, then east
+    pub fn spin(&mut self) {
+        for direction in [
+            Direction::North,
+            Direction::West,
+            Direction::South,
+            Direction::East,
+        ] {
+            self.tilt(direction);
+        }
+    }
+
+    pub fn north_load(&self) -> usize {
+        let height = self.round.len();
+        self.round
+            .iter()
+            .enumerate()
+            .map(|(y, row)| row.count_ones() as usize * (height - y))
+            .sum()
+    }
+
+    /// Skips ahead once the field starts repeating; None if `spins` is past `MAX_SPINS` and
+    /// no repeat shows up within them
+    pub fn north_load_after_spins(&self, spins: usize) -> Option<usize> {
+        let field = run_repeatedly(
+            self,
+            spins,
+            |field| field.clone(),
+            |field| {
+                let mut field = field.clone();
+                field.spin();
+                field
+            },
+        )?;
+        Some(field.north_load())
+    }
+
+    fn mask(&self) -> u128 {
+        match self.width {
+            MAX_WIDTH => u128::MAX,
+            width => (1 << width) - 1,
+        }
+    }
+
+    /// Rows nearer the wall have already settled by the time a row is moved, so each row's
+    /// rocks only need to travel until they hit something
+    fn tilt_vertically(&mut self, north: bool) {
+        let height = self.round.len();
+        let order: Vec<usize> = match north {
+            true => (0..height).collect(),
+            false => (0..height).rev().collect(),
+        };
+        for (i, y) in order.iter().enumerate().skip(1) {
+            let mut moving = self.round[*y];
+            self.round[*y] = 0;
+            let mut at = i;
+            while at > 0 && moving != 0 {
+                let (here, next) = (order[at], order[at - 1]);
+                let free = !(self.round[next] | self.cube[next]);
+                self.round[here] |= moving & !free;
+                moving &= free;
+                at -= 1;
+            }
+            self.round[order[at]] |= moving;
+        }
+    }
+
+    fn tilt_horizontally(&mut self, west: bool) {
+        let mask = self.mask();
+        for (round, cube) in self.round.iter_mut().zip(self.cube.iter()) {
+            loop {
+                let free = !(*round | cube) & mask;
+                let movable = match west {
+                    true => *round & (free << 1),
+                    false => *round & (free >> 1),
+                };
+                if movable == 0 {
+                    break;
+                }
+                *round &= !movable;
+                *round |= match west {
+                    true => movable >> 1,
+                    false => movable << 1,
+                };
+            }
+        }
+    }
+}
+
+impl TryFrom<&Collection<ParabolicFieldTile>> for PackedField {
+    type Error = FieldTooWideError;
+
+    fn try_from(collection: &Collection<ParabolicFieldTile>) -> Result<Self, Self::Error> {
+        let width = collection.count_columns();
+        if width > MAX_WIDTH {
+            return Err(FieldTooWideError(width));
+        }
+        let height = collection.count_rows();
+        let mut field = Self {
+            width,
+            round: vec![0; height],
+            cube: vec![0; height],
+        };
+        for y in 0..height {
+            for (x, tile) in collection.get_row(y as isize).into_iter().enumerate() {
+                match tile.tile_type() {
+                    ParabolicFieldTile::Round => field.round[y] |= 1 << x,
+                    ParabolicFieldTile::Cube => field.cube[y] |= 1 << x,
+                    ParabolicFieldTile::Empty => (),
+                }
+            }
+        }
+        Ok(field)
+    }
+}
+
+impl From<&PackedField> for Collection<ParabolicFieldTile> {
+    fn from(field: &PackedField) -> Self {
+        let rows = field
+            .round
+            .iter()
+            .zip(field.cube.iter())
+            .map(|(round, cube)| {
+                (0..field.width)
+                    .map(|x| match (round >> x & 1, cube >> x & 1) {
+                        (1, _) => ParabolicFieldTile::Round,
+                        (_, 1) => ParabolicFieldTile::Cube,
+                        _ => ParabolicFieldTile::Empty,
+                    })
+                    .collect()
+            })
+            .collect();
+        Collection::from_rows(rows)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shared::ParabolicFieldCollection;
+    use aoc_utils::parse_collection;
+    use test_case::test_case;
+
+    fn sample() -> Collection<ParabolicFieldTile> {
+        parse_collection(include_str!("../../data/sample_input.txt"))
+            .unwrap()
+            .1
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let collection = sample();
+        let field = PackedField::try_from(&collection).unwrap();
+        assert_eq!(Collection::from(&field), collection);
+        assert_eq!(field.north_load(), 104);
+    }
+
+    #[test_case(Direction::North)]
+    #[test_case(Direction::East)]
+    #[test_case(Direction::South)]
+    #[test_case(Direction::West)]
+    fn test_tilt_matches_collection(direction: Direction) {
+        let collection = ParabolicFieldCollection(sample());
+        let expected = match direction {
+            Direction::North => collection.tilt_north(),
+            Direction::East => collection.tilt_east(),
+            Direction::South => collection.tilt_south(),
+            Direction::West => collection.tilt_west(),
+        };
+        let mut field = PackedField::try_from(&collection.0).unwrap();
+        field.tilt(direction);
+        assert_eq!(Collection::from(&field), expected.0);
+    }
+
+    #[test_case(1, include_str!("../../data/sample_spin_1.txt"))]
+    #[test_case(2, include_str!("../../data/sample_spin_2.txt"))]
+    #[test_case(3, include_str!("../../data/sample_spin_3.txt"))]
+    fn test_spin(count: usize, expected: &str) {
+        let mut field = PackedField::try_from(&sample()).unwrap();
+        for _ in 0..count {
+            field.spin();
+        }
+        let expected: Collection<ParabolicFieldTile> = parse_collection(expected).unwrap().1;
+        assert_eq!(Collection::from(&field), expected);
+    }
+
+    #[test_case(0, 104)]
+    #[test_case(1, 87)]
+    #[test_case(1_000_000_000, 64)]
+    fn test_north_load_after_spins(spins: usize, exp: usize) {
+        let field = PackedField::try_from(&sample()).unwrap();
+        assert_eq!(field.north_load_after_spins(spins), Some(exp));
+    }
+
+    #[test]
+    fn test_full_width_row() {
+        let row = format!("O{}#", ".".repeat(MAX_WIDTH - 2));
+        let collection = parse_collection(&format!("{row}\n")).unwrap().1;
+        let mut field = PackedField::try_from(&collection).unwrap();
+        field.tilt(Direction::East);
+        let expected = format!("{}O#\n", ".".repeat(MAX_WIDTH - 2));
+        assert_eq!(
+            Collection::from(&field),
+            parse_collection(&expected).unwrap().1
+        );
+    }
+
+    #[test]
+    fn test_too_wide() {
+        let row = ".".repeat(MAX_WIDTH + 1);
+        let collection = parse_collection(&format!("{row}\n")).unwrap().1;
+        assert_eq!(
+            PackedField::try_from(&collection),
+            Err(FieldTooWideError(MAX_WIDTH + 1))
+        );
+    }
+}
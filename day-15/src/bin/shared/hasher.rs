@@ -0,0 +1,122 @@
+use std::hash::{BuildHasher, Hasher};
+
+/// Whether whitespace bytes take part in the hash
+#[allow(dead_code)]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Whitespace {
+    Skip,
+    Include,
+}
+
+/// The puzzle's HASH as a streaming `Hasher`: for every byte, add it, multiply and take the
+/// remainder. The state carries across `write` calls, so input can arrive in any chunks.
+#[derive(Debug, Clone, Copy)]
+pub struct AsciiHasher {
+    state: u64,
+    multiplier: u64,
+    modulus: u64,
+    whitespace: Whitespace,
+}
+
+impl Default for AsciiHasher {
+    fn default() -> Self {
+        BuildAsciiHasher::default().build_hasher()
+    }
+}
+
+impl Hasher for AsciiHasher {
+    fn finish(&self) -> u64 {
+        self.state
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            if self.whitespace == Whitespace::Skip && byte.is_ascii_whitespace() {
+                continue;
+            }
+            // Reduced in u128 so any multiplier and modulus fit without overflowing
+            let modulus = self.modulus as u128;
+            let sum = (self.state as u128 + *byte as u128) % modulus;
+            self.state = (sum * (self.multiplier as u128 % modulus) % modulus) as u64;
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct BuildAsciiHasher {
+    multiplier: u64,
+    modulus: u64,
+    whitespace: Whitespace,
+}
+
+impl BuildAsciiHasher {
+    /// Panics if `modulus` is zero
+    pub fn new(multiplier: u64, modulus: u64, whitespace: Whitespace) -> Self {
+        assert!(modulus > 0, "the modulus must be positive");
+        Self {
+            multiplier,
+            modulus,
+            whitespace,
+        }
+    }
+
+    /// Hashes the bytes of `inp` and nothing else, unlike `hash_one` on a `str`, which also
+    /// feeds the hasher a terminator byte
+    pub fn hash_str(&self, inp: &str) -> u64 {
+        let mut hasher = self.build_hasher();
+        hasher.write(inp.as_bytes());
+        hasher.finish()
+    }
+}
+
+/// The puzzle's parameters: multiply by 17, keep the remainder of 256, skip whitespace
+impl Default for BuildAsciiHasher {
+    fn default() -> Self {
+        Self::new(17, 256, Whitespace::Skip)
+    }
+}
+
+impl BuildHasher for BuildAsciiHasher {
+    type Hasher = AsciiHasher;
+
+    fn build_hasher(&self) -> Self::Hasher {
+        AsciiHasher {
+            state: 0,
+            multiplier: self.multiplier,
+            modulus: self.modulus,
+            whitespace: self.whitespace,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    #[test]
+    fn test_streaming() {
+        let mut hasher = AsciiHasher::default();
+        hasher.write(b"HA");
+        hasher.write(b"");
+        hasher.write(b"SH");
+        assert_eq!(hasher.finish(), 52);
+    }
+
+    #[test_case(Whitespace::Skip, 52)]
+    #[test_case(Whitespace::Include, 30)]
+    fn test_whitespace(whitespace: Whitespace, exp: u64) {
+        let builder = BuildAsciiHasher::new(17, 256, whitespace);
+        assert_eq!(builder.hash_str("HASH\n"), exp);
+    }
+
+    #[test_case(17, 256, 52)]
+    #[test_case(31, 1_000_000_007, 68_511_922)]
+    #[test_case(1, 1 << 32, 72 + 65 + 83 + 72)]
+    #[test_case(1 << 40, (1 << 40) + 1, 18)]
+    #[test_case(u64::MAX, u64::MAX - 58, 827_753_380)]
+    fn test_parameters(multiplier: u64, modulus: u64, exp: u64) {
+        let builder = BuildAsciiHasher::new(multiplier, modulus, Whitespace::Skip);
+        assert_eq!(builder.hash_str("HASH"), exp);
+    }
+}
@@ -1,14 +1,217 @@
+pub mod hasher;
+pub mod parser;
+
+use hasher::BuildAsciiHasher;
+use parser::parse_command;
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::hash::{BuildHasher, Hash, Hasher};
+
+/// Boxes the HASHMAP procedure spreads lenses over, one per possible `hash`
+pub const BOX_COUNT: usize = 256;
+
 pub fn hash(inp: &str) -> usize {
-    let mut current_value: usize = 0;
+    BuildAsciiHasher::default().hash_str(inp) as usize
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Operation {
+    Remove,
+    Add(usize),
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Command {
+    label: String,
+    operation: Operation,
+}
+
+/// A step of the initialization sequence that could not be parsed
+#[derive(Debug, PartialEq)]
+pub struct StepError {
+    pub index: usize,
+    pub step: String,
+}
 
-    inp.chars().for_each(|c| {
-        if !c.is_whitespace() {
-            current_value += c as usize;
-            current_value *= 17;
-            current_value %= 256;
+#[derive(Clone, Debug, PartialEq)]
+struct Lens {
+    label: String,
+    focal_length: usize,
+}
+
+impl Display for Lens {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[{} {}]", self.label, self.focal_length)
+    }
+}
+
+#[derive(Default, Clone, Debug)]
+struct LensBox(Vec<Lens>);
+impl LensBox {
+    fn remove_lens(&mut self, lens_label: &str) {
+        let existing = self.0.iter().position(|l| l.label == lens_label);
+        if let Some(idx) = existing {
+            self.0.remove(idx);
         }
-    });
-    current_value
+    }
+
+    fn add_lens(&mut self, lens: Lens) {
+        let existing = self.0.iter().position(|l| l.label == lens.label);
+        match existing {
+            Some(idx) => {
+                self.0[idx] = lens;
+            }
+            None => self.0.push(lens),
+        }
+    }
+}
+
+/// The row of boxes the HASHMAP procedure arranges lenses in, updated one command at a time
+#[derive(Clone, Debug)]
+pub struct LensBoxes(Vec<LensBox>);
+
+impl Default for LensBoxes {
+    fn default() -> Self {
+        Self(vec![LensBox::default(); BOX_COUNT])
+    }
+}
+
+// Part 1 only hashes
+#[allow(dead_code)]
+impl LensBoxes {
+    pub fn apply(&mut self, command: &Command) {
+        let this_box = &mut self.0[hash(&command.label)];
+        match command.operation {
+            Operation::Remove => this_box.remove_lens(&command.label),
+            Operation::Add(focal_length) => this_box.add_lens(Lens {
+                label: command.label.clone(),
+                focal_length,
+            }),
+        }
+    }
+
+    /// Parses and applies every comma separated step in order, stopping at the first bad one.
+    /// Whitespace around the whole sequence, such as a trailing newline, is ignored.
+    pub fn run(&mut self, sequence: &str) -> Result<(), StepError> {
+        self.run_with(sequence, |_, _| ())
+    }
+
+    /// Runs the sequence, writing out every non-empty box after each step in the same layout
+    /// as the puzzle's walkthrough
+    pub fn transcript(&mut self, sequence: &str) -> Result<String, StepError> {
+        let mut blocks = vec![];
+        self.run_with(sequence, |step, boxes| {
+            blocks.push(format!("After \"{}\":\n{}", step, boxes))
+        })?;
+        Ok(blocks.join("\n"))
+    }
+
+    fn run_with<F>(&mut self, sequence: &str, mut after_step: F) -> Result<(), StepError>
+    where
+        F: FnMut(&str, &Self),
+    {
+        if sequence.trim().is_empty() {
+            return Ok(());
+        }
+        for (index, step) in sequence.trim().split(',').enumerate() {
+            let (_, command) = parse_command(step).map_err(|_| StepError {
+                index,
+                step: step.to_string(),
+            })?;
+            self.apply(&command);
+            after_step(step, self);
+        }
+        Ok(())
+    }
+
+    pub fn focusing_power(&self) -> usize {
+        self.0
+            .iter()
+            .enumerate()
+            .flat_map(|(box_num, lens_box)| {
+                lens_box
+                    .0
+                    .iter()
+                    .enumerate()
+                    .map(move |(lens_num, lens)| (box_num + 1) * (lens_num + 1) * lens.focal_length)
+            })
+            .sum()
+    }
+}
+
+/// A lens label that hashes as its bytes alone, so a `HashMap` keyed on it buckets labels by
+/// exactly the puzzle's HASH
+#[derive(Debug, PartialEq, Eq, Clone)]
+struct Label(String);
+
+impl Hash for Label {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write(self.0.as_bytes())
+    }
+}
+
+/// The HASHMAP procedure on a standard `HashMap`. Each lens remembers when it was first
+/// inserted, which is all that's needed to recover its slot within its box.
+#[derive(Debug, Default)]
+pub struct LensMap {
+    lenses: HashMap<Label, (usize, usize), BuildAsciiHasher>,
+    inserted: usize,
+}
+
+#[allow(dead_code)]
+impl LensMap {
+    pub fn apply(&mut self, command: &Command) {
+        let label = Label(command.label.clone());
+        match command.operation {
+            Operation::Remove => {
+                self.lenses.remove(&label);
+            }
+            Operation::Add(focal_length) => {
+                let inserted = self.inserted;
+                let lens = self.lenses.entry(label).or_insert((inserted, focal_length));
+                lens.1 = focal_length;
+                self.inserted += 1;
+            }
+        }
+    }
+
+    pub fn focusing_power(&self) -> usize {
+        let hasher = self.lenses.hasher();
+        let mut boxes: HashMap<u64, Vec<(usize, usize)>> = HashMap::new();
+        for (label, lens) in &self.lenses {
+            boxes.entry(hasher.hash_one(label)).or_default().push(*lens);
+        }
+        boxes
+            .iter_mut()
+            .map(|(box_num, lenses)| {
+                lenses.sort_unstable();
+                lenses
+                    .iter()
+                    .enumerate()
+                    .map(|(slot, (_, focal_length))| {
+                        (*box_num as usize + 1) * (slot + 1) * focal_length
+                    })
+                    .sum::<usize>()
+            })
+            .sum()
+    }
+}
+
+/// One line per non-empty box, e.g. `Box 3: [ot 7] [ab 5]`
+impl Display for LensBoxes {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (box_num, lens_box) in self.0.iter().enumerate() {
+            if lens_box.0.is_empty() {
+                continue;
+            }
+            write!(f, "Box {}:", box_num)?;
+            for lens in &lens_box.0 {
+                write!(f, " {}", lens)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -35,4 +238,90 @@ mod tests {
         let actual = hash(inp);
         assert_eq!(actual, exp);
     }
+
+    #[test]
+    fn test_run_sample() {
+        let inp = include_str!("../../data/sample_input.txt");
+        let mut boxes = LensBoxes::default();
+        boxes.run(inp).unwrap();
+        assert_eq!(boxes.focusing_power(), 145);
+    }
+
+    #[test]
+    fn test_run_incrementally() {
+        let mut boxes = LensBoxes::default();
+        boxes.run("rn=1,cm-,qp=3").unwrap();
+        assert_eq!(boxes.focusing_power(), 1 + 2 * 3);
+        boxes.run("qp-,box12=10").unwrap();
+        assert_eq!(boxes.focusing_power(), 1 + (hash("box12") + 1) * 10);
+    }
+
+    #[test_case(""; "Empty")]
+    #[test_case(" \n"; "Whitespace only")]
+    fn test_run_empty(inp: &str) {
+        let mut boxes = LensBoxes::default();
+        assert_eq!(boxes.run(inp), Ok(()));
+        assert_eq!(boxes.focusing_power(), 0);
+    }
+
+    #[test_case("rn=1,cm-,qp=,pc=4", 2, "qp="; "Missing focal length")]
+    #[test_case("rn=1,,cm-", 1, ""; "Empty step")]
+    #[test_case("rn=1\ncm-", 0, "rn=1\ncm-"; "Newline inside")]
+    fn test_run_err(inp: &str, exp_index: usize, exp_step: &str) {
+        let mut boxes = LensBoxes::default();
+        let actual = boxes.run(inp);
+        assert_eq!(
+            actual,
+            Err(StepError {
+                index: exp_index,
+                step: exp_step.to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn test_transcript() {
+        let inp = include_str!("../../data/sample_input.txt");
+        let expected = include_str!("../../data/sample_transcript.txt");
+        let actual = LensBoxes::default().transcript(inp).unwrap();
+        for (actual_line, expected_line) in actual.lines().zip(expected.lines()) {
+            assert_eq!(actual_line, expected_line);
+        }
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_transcript_stops_at_bad_step() {
+        let actual = LensBoxes::default().transcript("rn=1,cm");
+        assert_eq!(
+            actual,
+            Err(StepError {
+                index: 1,
+                step: "cm".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn test_label_hash_matches_hash() {
+        let builder = BuildAsciiHasher::default();
+        for label in ["rn", "cm", "qp", "pc", "ot", "ab"] {
+            let actual = builder.hash_one(Label(label.to_string()));
+            assert_eq!(actual as usize, hash(label));
+        }
+    }
+
+    #[test_case(include_str!("../../data/sample_input.txt"), 145; "Sample")]
+    #[test_case("rn=1,cm=2,rn-,rn=3,cm=4,qp=5,cm-,rn=6", 16; "Colliding labels")]
+    fn test_lens_map_matches_lens_boxes(inp: &str, exp: usize) {
+        let mut boxes = LensBoxes::default();
+        let mut map = LensMap::default();
+        for step in inp.trim().split(',') {
+            let command = parse_command(step).unwrap().1;
+            boxes.apply(&command);
+            map.apply(&command);
+        }
+        assert_eq!(boxes.focusing_power(), exp);
+        assert_eq!(map.focusing_power(), exp);
+    }
 }
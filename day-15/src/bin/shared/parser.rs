@@ -0,0 +1,52 @@
+use crate::shared::{Command, Operation};
+use nom::{
+    branch::alt,
+    character::complete::{alphanumeric1, char, digit1},
+    combinator::{all_consuming, map, map_res, value},
+    sequence::{preceded, tuple},
+    IResult,
+};
+
+fn parse_operation(inp: &str) -> IResult<&str, Operation> {
+    alt((
+        value(Operation::Remove, char('-')),
+        map(
+            preceded(char('='), map_res(digit1, str::parse::<usize>)),
+            Operation::Add,
+        ),
+    ))(inp)
+}
+
+pub fn parse_command(inp: &str) -> IResult<&str, Command> {
+    map(
+        all_consuming(tuple((alphanumeric1, parse_operation))),
+        |(label, operation): (&str, Operation)| Command {
+            label: label.to_string(),
+            operation,
+        },
+    )(inp)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    #[test_case("rn=1", "rn", Operation::Add(1); "Add")]
+    #[test_case("cm-", "cm", Operation::Remove; "Remove")]
+    #[test_case("ab12=345", "ab12", Operation::Add(345); "Multi-digit")]
+    fn test_parse_command(inp: &str, exp_label: &str, exp_operation: Operation) {
+        let actual = parse_command(inp).unwrap().1;
+        assert_eq!(actual.label, exp_label);
+        assert_eq!(actual.operation, exp_operation);
+    }
+
+    #[test_case("rn="; "Missing focal length")]
+    #[test_case("rn=1\n"; "Trailing newline")]
+    #[test_case("r-n=1"; "Bad label")]
+    #[test_case("rn"; "Missing operation")]
+    #[test_case("rn=99999999999999999999999"; "Focal length overflow")]
+    fn test_parse_command_err(inp: &str) {
+        assert!(parse_command(inp).is_err());
+    }
+}
@@ -1,60 +1,44 @@
 mod shared;
-use std::thread;
 
+use aoc_utils::parallel::{default_workers, scan_max};
 use aoc_utils::{parse_collection, Collection, Direction, Loc};
-use shared::{Energize, MirrorTile};
+use shared::{Contraption, MirrorTile};
 
 fn main() {
     let inp = include_str!("../data/puzzle_input.txt");
     let collection: Collection<MirrorTile> = parse_collection(inp).unwrap().1;
-    let actual = test_all_entrypoints(collection);
+    let actual = test_all_entrypoints(Contraption::from(&collection));
     println!("{}", actual);
 }
 
-fn test_all_entrypoints(collection: Collection<MirrorTile>) -> usize {
-    let mut runs: Vec<usize> = vec![];
-    let max_row = collection.count_rows() - 1;
-    let max_column = collection.count_columns() - 1;
-    let mut threads = vec![];
+fn test_all_entrypoints(contraption: Contraption) -> usize {
+    let max_row = contraption.height() - 1;
+    let max_column = contraption.width() - 1;
+    let mut entrypoints = vec![];
     for row_num in 0..=max_row {
-        let collection = collection.clone();
-        threads.push(thread::spawn(move || {
-            run_single_entrypoint(collection, 0, row_num, Direction::East)
-        }))
-    }
-    for row_num in 0..=max_row {
-        let collection = collection.clone();
-        threads.push(thread::spawn(move || {
-            run_single_entrypoint(collection, max_column, row_num, Direction::West)
-        }))
+        entrypoints.push((0, row_num, Direction::East));
+        entrypoints.push((max_column, row_num, Direction::West));
     }
     for col_num in 0..=max_column {
-        let collection = collection.clone();
-        threads.push(thread::spawn(move || {
-            run_single_entrypoint(collection, col_num, 0, Direction::South)
-        }))
-    }
-    for col_num in 0..=max_column {
-        let collection = collection.clone();
-        threads.push(thread::spawn(move || {
-            run_single_entrypoint(collection, col_num, max_row, Direction::North)
-        }))
-    }
-    println!("Threads: {}", threads.len());
-    for thread in threads {
-        runs.push(thread.join().unwrap());
+        entrypoints.push((col_num, 0, Direction::South));
+        entrypoints.push((col_num, max_row, Direction::North));
     }
-    runs.sort();
-    *runs.last().unwrap()
+    scan_max(
+        &contraption,
+        &entrypoints,
+        default_workers(),
+        |contraption, (x, y, direction)| run_single_entrypoint(contraption, *x, *y, *direction),
+    )
+    .unwrap()
 }
 
 fn run_single_entrypoint(
-    collection: Collection<MirrorTile>,
+    contraption: &Contraption,
     x: usize,
     y: usize,
     direction: Direction,
 ) -> usize {
-    collection.energize_tiles(Loc::new(x, y), direction).len()
+    contraption.energized_count(Loc::new(x as isize, y as isize), direction)
 }
 
 #[cfg(test)]
@@ -65,7 +49,7 @@ mod tests {
     fn test_all_entrypoints_sample() {
         let inp = include_str!("../data/sample_input.txt");
         let collection: Collection<MirrorTile> = parse_collection(inp).unwrap().1;
-        let actual = test_all_entrypoints(collection);
+        let actual = test_all_entrypoints(Contraption::from(&collection));
         assert_eq!(actual, 51);
     }
 }
@@ -1,114 +1,5 @@
 use aoc_utils::{BadTileTypeError, Collection, Direction, Loc, ParseableCharacters};
-use uuid::Uuid;
-
-#[derive(Debug, PartialEq)]
-enum LightBeamMovement {
-    Forward,
-    Bend,
-    Split(Loc, Direction),
-    Complete,
-}
-
-#[allow(dead_code)]
-#[derive(Debug, Clone)]
-struct LightBeam<'a> {
-    location: Loc,
-    collection: &'a Collection<MirrorTile>,
-    direction: Direction,
-    enabled: bool,
-    id: Uuid,
-}
-
-#[derive(Clone, Debug, PartialEq)]
-struct HistoryEntry((Loc, Direction));
 
-impl<'a> LightBeam<'a> {
-    fn new(location: Loc, collection: &'a Collection<MirrorTile>, direction: Direction) -> Self {
-        let id = Uuid::new_v4();
-        // println!("Spawned beam {} ", id);
-        Self {
-            location,
-            collection,
-            direction,
-            enabled: true,
-            id,
-        }
-    }
-
-    fn disable(&mut self) {
-        self.enabled = false;
-    }
-
-    #[allow(dead_code)]
-    fn enable(&mut self) {
-        self.enabled = true;
-    }
-
-    fn move_next(&mut self) -> LightBeamMovement {
-        let next_loc = self.location.get_neighbor(self.direction);
-        // if self.enabled {
-        //     dbg!(self.id, self.location, next_loc, self.direction);
-        //     println!();
-        // }
-        match next_loc {
-            Some(new_loc) => {
-                let movement_type = match self.collection.get_tile(new_loc) {
-                    Some(new_tile) => match new_tile.tile_type() {
-                        MirrorTile::Empty => LightBeamMovement::Forward,
-                        MirrorTile::Mirror(slope) => {
-                            match slope {
-                                Slope::Positive => match self.direction {
-                                    Direction::North | Direction::South => {
-                                        self.direction = self.direction.rotate_clockwise()
-                                    }
-                                    Direction::East | Direction::West => {
-                                        self.direction = self.direction.rotate_counterclockwise()
-                                    }
-                                },
-                                Slope::Negative => match self.direction {
-                                    Direction::North | Direction::South => {
-                                        self.direction = self.direction.rotate_counterclockwise()
-                                    }
-                                    Direction::East | Direction::West => {
-                                        self.direction = self.direction.rotate_clockwise()
-                                    }
-                                },
-                            };
-                            LightBeamMovement::Bend
-                        }
-                        MirrorTile::Splitter(split_type) => match split_type {
-                            SplitterType::Horizontal => match self.direction {
-                                Direction::North | Direction::South => {
-                                    self.direction = Direction::West;
-                                    LightBeamMovement::Split(new_loc, Direction::East)
-                                }
-                                Direction::East | Direction::West => LightBeamMovement::Forward,
-                            },
-                            SplitterType::Vertical => match self.direction {
-                                Direction::East | Direction::West => {
-                                    self.direction = Direction::North;
-                                    LightBeamMovement::Split(new_loc, Direction::South)
-                                }
-                                Direction::North | Direction::South => LightBeamMovement::Forward,
-                            },
-                        },
-                    },
-                    None => LightBeamMovement::Complete,
-                };
-                if movement_type != LightBeamMovement::Complete {
-                    self.location = new_loc;
-                } else {
-                    // println!("id: {} COMPLETE", self.id)
-                }
-                movement_type
-            }
-            None => {
-                // println!("id: {} COMPLETE", self.id);
-                LightBeamMovement::Complete
-            }
-        }
-    }
-}
 #[derive(Debug, PartialEq, Copy, Clone, Ord, PartialOrd, Eq)]
 pub enum Slope {
     Positive, // /
@@ -146,59 +37,197 @@ impl ParseableCharacters for MirrorTile {
         vec!['.', '|', '-', '\\', '/']
     }
 }
+/// A set of directions, one bit each
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Default)]
+pub struct Directions(u8);
+
+impl Directions {
+    pub fn none() -> Self {
+        Self(0)
+    }
+
+    pub fn with(self, direction: Direction) -> Self {
+        Self(self.0 | Self::bit(direction))
+    }
+
+    pub fn contains(&self, direction: Direction) -> bool {
+        self.0 & Self::bit(direction) != 0
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = Direction> + '_ {
+        [
+            Direction::North,
+            Direction::East,
+            Direction::South,
+            Direction::West,
+        ]
+        .into_iter()
+        .filter(|d| self.contains(*d))
+    }
+
+    fn bit(direction: Direction) -> u8 {
+        match direction {
+            Direction::North => 1,
+            Direction::East => 2,
+            Direction::South => 4,
+            Direction::West => 8,
+        }
+    }
+}
+
+impl From<Direction> for Directions {
+    fn from(direction: Direction) -> Self {
+        Self::none().with(direction)
+    }
+}
+
+/// How a tile bends light. Any tile type that implements this, along with the parsing traits,
+/// can be laid out in a `Contraption`.
+pub trait Optic {
+    /// Directions a beam leaves this tile in, having entered it travelling `direction`.
+    /// No directions means the beam is absorbed.
+    fn deflect(&self, direction: Direction) -> Directions;
+}
+
+impl Optic for MirrorTile {
+    fn deflect(&self, direction: Direction) -> Directions {
+        match self {
+            MirrorTile::Empty => direction.into(),
+            MirrorTile::Mirror(Slope::Positive) => match direction {
+                Direction::North | Direction::South => direction.rotate_clockwise().into(),
+                Direction::East | Direction::West => direction.rotate_counterclockwise().into(),
+            },
+            MirrorTile::Mirror(Slope::Negative) => match direction {
+                Direction::North | Direction::South => direction.rotate_counterclockwise().into(),
+                Direction::East | Direction::West => direction.rotate_clockwise().into(),
+            },
+            MirrorTile::Splitter(SplitterType::Horizontal) => match direction {
+                Direction::North | Direction::South => {
+                    Directions::from(Direction::West).with(Direction::East)
+                }
+                Direction::East | Direction::West => direction.into(),
+            },
+            MirrorTile::Splitter(SplitterType::Vertical) => match direction {
+                Direction::East | Direction::West => {
+                    Directions::from(Direction::North).with(Direction::South)
+                }
+                Direction::North | Direction::South => direction.into(),
+            },
+        }
+    }
+}
+
+// Part 2 fires its beams through a `Contraption` directly
+#[allow(dead_code)]
 pub trait Energize {
     fn energize_tiles(&self, starting_loc: Loc, starting_direction: Direction) -> Vec<Loc>;
 }
 
-impl Energize for Collection<MirrorTile> {
+impl<T: Optic + Copy> Energize for Collection<T> {
     fn energize_tiles(&self, starting_loc: Loc, starting_direction: Direction) -> Vec<Loc> {
-        let mut light_beams: Vec<LightBeam> = vec![];
-        let starting_direction = match self.get_tile(starting_loc).unwrap().tile_type() {
-            MirrorTile::Mirror(_) => Direction::South,
-            MirrorTile::Splitter(_) => Direction::South,
-            MirrorTile::Empty => starting_direction,
-        };
-        let mut location_history: Vec<HistoryEntry> = vec![];
-        let initial_light_beam = LightBeam::new(starting_loc, self, starting_direction);
-        let mut beams_to_add: Vec<LightBeam> = vec![];
-        light_beams.push(initial_light_beam);
-        loop {
-            let enabled_beams = light_beams.iter_mut().filter(|b| b.enabled);
-            for beam in enabled_beams {
-                location_history.push(HistoryEntry((beam.location, beam.direction)));
-                let next = beam.move_next();
-                match next {
-                    LightBeamMovement::Forward | LightBeamMovement::Bend => {}
-                    LightBeamMovement::Split(new_loc, new_direction) => {
-                        beams_to_add.push(LightBeam::new(new_loc, self, new_direction));
-                    }
-                    LightBeamMovement::Complete => beam.disable(),
-                }
-                if location_history.contains(&HistoryEntry((beam.location, beam.direction))) {
-                    beam.disable();
+        Contraption::from(self).energize_tiles(starting_loc, starting_direction)
+    }
+}
+
+/// The tiles laid out row by row, so a beam can look up its next tile by index rather than
+/// searching the collection. Build it once when firing many beams through the same layout.
+#[derive(Debug, Clone)]
+pub struct Contraption<T = MirrorTile> {
+    width: usize,
+    height: usize,
+    tiles: Vec<T>,
+}
+
+impl<T: Copy> From<&Collection<T>> for Contraption<T> {
+    fn from(collection: &Collection<T>) -> Self {
+        let width = collection.count_columns();
+        let height = collection.count_rows();
+        let tiles = (0..height as isize)
+            .flat_map(|y| collection.get_row(y).into_iter().map(|t| *t.tile_type()))
+            .collect();
+        Self {
+            width,
+            height,
+            tiles,
+        }
+    }
+}
+
+// Part 1 only energizes through the `Energize` trait
+#[allow(dead_code)]
+impl<T: Optic> Contraption<T> {
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    fn index(&self, loc: Loc) -> Option<usize> {
+        let (x, y) = (loc.get_x(), loc.get_y());
+        match (0..self.width as isize).contains(&x) && (0..self.height as isize).contains(&y) {
+            true => Some(y as usize * self.width + x as usize),
+            false => None,
+        }
+    }
+
+    /// For every tile, the directions beams have entered it in. The starting tile deflects or
+    /// splits the beam like any other.
+    fn trace(&self, starting_loc: Loc, starting_direction: Direction) -> Vec<Directions> {
+        let mut visited = vec![Directions::none(); self.tiles.len()];
+        let start = self.index(starting_loc).unwrap();
+        visited[start] = starting_direction.into();
+        let mut beams = vec![(starting_loc, starting_direction)];
+        while let Some((loc, direction)) = beams.pop() {
+            let outgoing = self.tiles[self.index(loc).unwrap()].deflect(direction);
+            for next_direction in outgoing.iter() {
+                let Some(next_loc) = loc.get_neighbor(next_direction) else {
+                    continue;
+                };
+                let Some(next_index) = self.index(next_loc) else {
                     continue;
+                };
+                if !visited[next_index].contains(next_direction) {
+                    visited[next_index] = visited[next_index].with(next_direction);
+                    beams.push((next_loc, next_direction));
                 }
             }
-            light_beams.extend(beams_to_add.clone());
-            beams_to_add.clear();
-            if light_beams.iter().filter(|b| b.enabled).count() == 0 {
-                break;
-            }
         }
-        let mut energized_list: Vec<Loc> = location_history
+        visited
+    }
+
+    /// Follows every beam with a work list, remembering which directions have already passed
+    /// through each tile so loops and merging beams are only walked once
+    pub fn energize_tiles(&self, starting_loc: Loc, starting_direction: Direction) -> Vec<Loc> {
+        let mut energized_list: Vec<Loc> = self
+            .trace(starting_loc, starting_direction)
             .iter()
-            .map(|loc_hist| loc_hist.0 .0)
+            .enumerate()
+            .filter(|(_, directions)| !directions.is_empty())
+            .map(|(i, _)| Loc::new((i % self.width) as isize, (i / self.width) as isize))
             .collect();
         energized_list.sort();
-        energized_list.dedup();
         energized_list
     }
+
+    pub fn energized_count(&self, starting_loc: Loc, starting_direction: Direction) -> usize {
+        self.trace(starting_loc, starting_direction)
+            .iter()
+            .filter(|directions| !directions.is_empty())
+            .count()
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use aoc_utils::parse_collection;
+    use test_case::test_case;
 
     #[test]
     fn test_parse_sample() {
@@ -212,7 +241,111 @@ mod tests {
     fn test_energize_sample() {
         let inp = include_str!("../../data/sample_input.txt");
         let collection: (&str, Collection<MirrorTile>) = parse_collection(inp).unwrap();
-        let actual = collection.1.energize_tiles(Loc::new(0,0), Direction::East);
+        let actual = collection.1.energize_tiles(Loc::new(0, 0), Direction::East);
         assert_eq!(actual.len(), 46);
     }
+
+    #[test]
+    fn test_energize_loop() {
+        let inp = ".|..\\\n.....\n.\\../\n";
+        let collection: Collection<MirrorTile> = parse_collection(inp).unwrap().1;
+        let contraption = Contraption::from(&collection);
+        assert_eq!(
+            contraption.energized_count(Loc::new(0, 0), Direction::East),
+            11
+        );
+    }
+
+    fn energize_from_edge(tile: char, start: Loc, direction: Direction) -> Vec<Loc> {
+        let mut rows = vec![vec!['.'; 3]; 3];
+        rows[start.get_y() as usize][start.get_x() as usize] = tile;
+        let inp: String = rows
+            .iter()
+            .map(|row| row.iter().collect::<String>() + "\n")
+            .collect();
+        let collection: Collection<MirrorTile> = parse_collection(&inp).unwrap().1;
+        collection.energize_tiles(start, direction)
+    }
+
+    #[test_case('.', Loc::new(0, 1), Direction::East, vec![(0, 1), (1, 1), (2, 1)]; "West edge, empty")]
+    #[test_case('/', Loc::new(0, 1), Direction::East, vec![(0, 0), (0, 1)]; "West edge, positive mirror")]
+    #[test_case('\\', Loc::new(0, 1), Direction::East, vec![(0, 1), (0, 2)]; "West edge, negative mirror")]
+    #[test_case('-', Loc::new(0, 1), Direction::East, vec![(0, 1), (1, 1), (2, 1)]; "West edge, horizontal splitter")]
+    #[test_case('|', Loc::new(0, 1), Direction::East, vec![(0, 0), (0, 1), (0, 2)]; "West edge, vertical splitter")]
+    #[test_case('.', Loc::new(2, 1), Direction::West, vec![(0, 1), (1, 1), (2, 1)]; "East edge, empty")]
+    #[test_case('/', Loc::new(2, 1), Direction::West, vec![(2, 1), (2, 2)]; "East edge, positive mirror")]
+    #[test_case('\\', Loc::new(2, 1), Direction::West, vec![(2, 0), (2, 1)]; "East edge, negative mirror")]
+    #[test_case('-', Loc::new(2, 1), Direction::West, vec![(0, 1), (1, 1), (2, 1)]; "East edge, horizontal splitter")]
+    #[test_case('|', Loc::new(2, 1), Direction::West, vec![(2, 0), (2, 1), (2, 2)]; "East edge, vertical splitter")]
+    #[test_case('.', Loc::new(1, 0), Direction::South, vec![(1, 0), (1, 1), (1, 2)]; "North edge, empty")]
+    #[test_case('/', Loc::new(1, 0), Direction::South, vec![(0, 0), (1, 0)]; "North edge, positive mirror")]
+    #[test_case('\\', Loc::new(1, 0), Direction::South, vec![(1, 0), (2, 0)]; "North edge, negative mirror")]
+    #[test_case('-', Loc::new(1, 0), Direction::South, vec![(0, 0), (1, 0), (2, 0)]; "North edge, horizontal splitter")]
+    #[test_case('|', Loc::new(1, 0), Direction::South, vec![(1, 0), (1, 1), (1, 2)]; "North edge, vertical splitter")]
+    #[test_case('.', Loc::new(1, 2), Direction::North, vec![(1, 0), (1, 1), (1, 2)]; "South edge, empty")]
+    #[test_case('/', Loc::new(1, 2), Direction::North, vec![(1, 2), (2, 2)]; "South edge, positive mirror")]
+    #[test_case('\\', Loc::new(1, 2), Direction::North, vec![(0, 2), (1, 2)]; "South edge, negative mirror")]
+    #[test_case('-', Loc::new(1, 2), Direction::North, vec![(0, 2), (1, 2), (2, 2)]; "South edge, horizontal splitter")]
+    #[test_case('|', Loc::new(1, 2), Direction::North, vec![(1, 0), (1, 1), (1, 2)]; "South edge, vertical splitter")]
+    fn test_start_on_edge(tile: char, start: Loc, direction: Direction, exp: Vec<(isize, isize)>) {
+        let actual = energize_from_edge(tile, start, direction);
+        let expected: Vec<Loc> = exp.iter().map(|(x, y)| Loc::new(*x, *y)).collect();
+        assert_eq!(actual, expected);
+    }
+
+    /// Tiles beyond the puzzle's, plugged in without touching the simulator
+    #[derive(Debug, PartialEq, Copy, Clone)]
+    enum LabTile {
+        Standard(MirrorTile),
+        Absorber,
+        OneWay(Direction),
+        Rotator,
+    }
+    impl TryFrom<char> for LabTile {
+        type Error = BadTileTypeError;
+
+        fn try_from(value: char) -> Result<Self, Self::Error> {
+            match value {
+                '#' => Ok(Self::Absorber),
+                '^' => Ok(Self::OneWay(Direction::North)),
+                '>' => Ok(Self::OneWay(Direction::East)),
+                'v' => Ok(Self::OneWay(Direction::South)),
+                '<' => Ok(Self::OneWay(Direction::West)),
+                '@' => Ok(Self::Rotator),
+                _ => Ok(Self::Standard(MirrorTile::try_from(value)?)),
+            }
+        }
+    }
+    impl ParseableCharacters for LabTile {
+        fn valid_chars() -> Vec<char> {
+            let mut chars = MirrorTile::valid_chars();
+            chars.extend(['#', '^', '>', 'v', '<', '@']);
+            chars
+        }
+    }
+    impl Optic for LabTile {
+        fn deflect(&self, direction: Direction) -> Directions {
+            match self {
+                LabTile::Standard(tile) => tile.deflect(direction),
+                LabTile::Absorber => Directions::none(),
+                LabTile::OneWay(allowed) if *allowed == direction => direction.into(),
+                LabTile::OneWay(_) => Directions::none(),
+                LabTile::Rotator => direction.rotate_clockwise().into(),
+            }
+        }
+    }
+
+    #[test_case("..#..\n", 3; "Absorber")]
+    #[test_case(".>.<.\n", 4; "One way prism against the beam")]
+    #[test_case(".>.>.\n", 5; "One way prism with the beam")]
+    #[test_case("..@..\n..|..\n", 4; "Rotator")]
+    #[test_case(".@...\n.@-..\n", 4; "Two rotators")]
+    fn test_custom_tiles(inp: &str, exp: usize) {
+        let collection: Collection<LabTile> = parse_collection(inp).unwrap().1;
+        let contraption = Contraption::from(&collection);
+        assert_eq!(
+            contraption.energized_count(Loc::new(0, 0), Direction::East),
+            exp
+        );
+    }
 }
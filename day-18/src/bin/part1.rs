@@ -1,13 +1,11 @@
 mod shared;
-use crate::shared::parse_instruction_set;
-
+use crate::shared::{parse_instruction_set, Reading};
 
 fn main() {
     let inp = include_str!("../data/puzzle_input.txt");
     let instruction_set = parse_instruction_set(inp).unwrap().1;
-    let outline = instruction_set.draw_polygon();
-    let perimiter = instruction_set.get_perimiter();
-    // Shoelace formula for area, then add the perimiter back in
-    let actual = outline.get_area() as usize + (perimiter / 2) + 1;
-    println!("{}", actual);
+    match instruction_set.plan(Reading::Literal) {
+        Ok(plan) => println!("{}", plan.lagoon_size()),
+        Err(e) => eprintln!("Invalid dig plan: {:?}", e),
+    }
 }
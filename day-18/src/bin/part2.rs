@@ -1,14 +1,13 @@
 mod shared;
-use crate::shared::parse_true_instruction_set;
+use crate::shared::{parse_instruction_set, Reading};
 
 fn main() {
     let inp = include_str!("../data/puzzle_input.txt");
-    let instruction_set = parse_true_instruction_set(inp).unwrap().1;
-    let outline = instruction_set.draw_polygon();
-    let perimiter= instruction_set.get_perimiter();
-    // Shoelace formula for area, then add the perimiter back in
-    let actual = outline.get_area() + (perimiter / 2) + 1;
-    println!("{}", actual);
+    let instruction_set = parse_instruction_set(inp).unwrap().1;
+    match instruction_set.plan(Reading::Hex) {
+        Ok(plan) => println!("{}", plan.lagoon_size()),
+        Err(e) => eprintln!("Invalid dig plan: {:?}", e),
+    }
 }
 
 #[cfg(test)]
@@ -16,12 +15,10 @@ mod tests {
     use super::*;
 
     #[test]
-    fn test_get_area() {
+    fn test_lagoon_size() {
         let inp = include_str!("../data/sample_input.txt");
-        let instruction_set = parse_true_instruction_set(inp).unwrap().1;
-        let outline = instruction_set.draw_polygon();
-        let perimiter = instruction_set.get_perimiter();
-        let actual = outline.get_area() + (perimiter / 2) + 1;
+        let instruction_set = parse_instruction_set(inp).unwrap().1;
+        let actual = instruction_set.plan(Reading::Hex).unwrap().lagoon_size();
         assert_eq!(actual, 952408144115);
     }
 }
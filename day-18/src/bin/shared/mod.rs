@@ -1,20 +1,27 @@
 use aoc_utils::{Direction, Loc};
 use nom::{
-    bytes::complete::{tag, take_until, take_while_m_n},
-    character::complete::{digit1, hex_digit1, newline, one_of},
+    bytes::complete::{tag, take_while_m_n},
+    character::complete::{digit1, newline, one_of},
     combinator::{map, map_res},
     multi::many1,
     sequence::{delimited, terminated, tuple},
     IResult,
 };
 
-#[derive(Debug, PartialEq, Default)]
+#[derive(Debug, PartialEq, Default, Clone, Copy)]
 pub struct Color {
     pub red: u8,
     pub green: u8,
     pub blue: u8,
 }
 
+impl Color {
+    /// The six hex digits as one number, red in the high byte
+    pub fn hex(&self) -> u32 {
+        (self.red as u32) << 16 | (self.green as u32) << 8 | self.blue as u32
+    }
+}
+
 fn from_hex(input: &str) -> Result<u8, std::num::ParseIntError> {
     u8::from_str_radix(input, 16)
 }
@@ -51,7 +58,8 @@ impl Polygon {
         let first = self.0[0];
         let last = self.0.last().unwrap();
         sum += (last.get_x() * first.get_y()) - (first.get_x() * last.get_y());
-        (sum / 2) as usize
+        // Counterclockwise outlines come out negative
+        sum.unsigned_abs() / 2
     }
 }
 impl std::fmt::Debug for Polygon {
@@ -82,29 +90,135 @@ impl std::fmt::Debug for Polygon {
     }
 }
 
-pub struct InstructionSet(Vec<Instruction>);
-impl InstructionSet {
-    pub fn len(&self) -> usize {
-        self.0.len()
-    }
+/// How to read each line of the dig plan
+#[allow(dead_code)]
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum Reading {
+    /// The direction letter and count as written
+    Literal,
+    /// The first five hex digits are the count and the last one the direction
+    Hex,
+}
 
+/// Why a dig plan can't be dug. Indices are zero-based positions in the instruction set.
+#[derive(Debug, PartialEq)]
+pub enum PlanError {
+    /// The last hex digit of the instruction isn't 0-3
+    BadHexDirection { index: usize, digit: u8 },
+    /// The instruction doesn't move at all
+    ZeroLength { index: usize },
+    /// Following every instruction ends at `end` instead of back at the start
+    NotClosed { end: Loc },
+    /// The trench dug by instruction `index` runs into the one dug by the earlier instruction
+    /// `other`
+    SelfIntersecting { index: usize, other: usize },
+}
+
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub struct Step {
+    pub direction: Direction,
+    pub count: usize,
+}
+
+/// A validated plan: it returns to its start and its trench never crosses or doubles back on itself
+pub struct DigPlan(Vec<Step>);
+impl DigPlan {
     pub fn get_perimiter(&self) -> usize {
-        self.0.iter().map(|i| i.count).sum()
+        self.0.iter().map(|s| s.count).sum()
     }
 
     pub fn draw_polygon(&self) -> Polygon {
-        let mut instructions = vec![];
-        let mut current_loc = Loc::new(0, 0);
-        self.0.iter().for_each(|i| {
-            current_loc = current_loc.get_nearby(i.direction, i.count as isize);
-            instructions.push(current_loc);
-        });
-        Polygon(instructions)
+        Polygon(corners(&self.0))
+    }
+
+    /// Tiles inside the trench plus the trench itself
+    pub fn lagoon_size(&self) -> usize {
+        // Shoelace formula for area, then add the perimiter back in
+        self.draw_polygon().get_area() + (self.get_perimiter() / 2) + 1
+    }
+}
+
+/// The corner reached after each step, starting from the origin
+fn corners(steps: &[Step]) -> Vec<Loc> {
+    let mut current_loc = Loc::new(0, 0);
+    steps
+        .iter()
+        .map(|s| {
+            current_loc = current_loc.get_nearby(s.direction, s.count as isize);
+            current_loc
+        })
+        .collect()
+}
+
+/// Corners of the box a straight trench covers: (min x, max x, min y, max y)
+fn bounds(from: Loc, to: Loc) -> (isize, isize, isize, isize) {
+    (
+        from.get_x().min(to.get_x()),
+        from.get_x().max(to.get_x()),
+        from.get_y().min(to.get_y()),
+        from.get_y().max(to.get_y()),
+    )
+}
+
+fn touches(a: (isize, isize, isize, isize), b: (isize, isize, isize, isize)) -> bool {
+    a.0 <= b.1 && b.0 <= a.1 && a.2 <= b.3 && b.2 <= a.3
+}
+
+fn validate(steps: Vec<Step>) -> Result<DigPlan, PlanError> {
+    if let Some(index) = steps.iter().position(|s| s.count == 0) {
+        return Err(PlanError::ZeroLength { index });
+    }
+    let ends = corners(&steps);
+    let end = ends.last().copied().unwrap_or(Loc::new(0, 0));
+    if end != Loc::new(0, 0) {
+        return Err(PlanError::NotClosed { end });
+    }
+    let starts = std::iter::once(Loc::new(0, 0)).chain(ends.iter().copied());
+    let trenches: Vec<_> = starts
+        .zip(ends.iter())
+        .map(|(from, to)| bounds(from, *to))
+        .collect();
+    let last = steps.len().saturating_sub(1);
+    for index in 1..steps.len() {
+        for other in 0..index {
+            // Neighbouring trenches share a corner, so they only clash if one doubles back
+            let neighbours = other + 1 == index || (other == 0 && index == last);
+            let clash = match neighbours {
+                true => {
+                    let reversed = steps[index].direction.rotate_clockwise().rotate_clockwise();
+                    steps[other].direction == reversed
+                }
+                false => touches(trenches[index], trenches[other]),
+            };
+            if clash {
+                return Err(PlanError::SelfIntersecting { index, other });
+            }
+        }
+    }
+    Ok(DigPlan(steps))
+}
+
+pub struct InstructionSet(Vec<Instruction>);
+impl InstructionSet {
+    pub fn steps(&self, reading: Reading) -> Result<Vec<Step>, PlanError> {
+        self.0
+            .iter()
+            .enumerate()
+            .map(|(index, i)| {
+                i.step(reading)
+                    .map_err(|digit| PlanError::BadHexDirection { index, digit })
+            })
+            .collect()
+    }
+
+    /// Reads every instruction and checks the result is a single closed loop
+    pub fn plan(&self, reading: Reading) -> Result<DigPlan, PlanError> {
+        validate(self.steps(reading)?)
     }
 }
 
 #[derive(Debug, PartialEq)]
-struct Instruction {
+pub struct Instruction {
     direction: Direction,
     count: usize,
     color: Color,
@@ -118,13 +232,37 @@ impl Instruction {
             color,
         }
     }
+
+    /// The step this line describes under `reading`, or the last hex digit if it isn't a direction
+    pub fn step(&self, reading: Reading) -> Result<Step, u8> {
+        match reading {
+            Reading::Literal => Ok(Step {
+                direction: self.direction,
+                count: self.count,
+            }),
+            Reading::Hex => {
+                let hex = self.color.hex();
+                let direction = match (hex & 0xf) as u8 {
+                    0 => Direction::East,
+                    1 => Direction::South,
+                    2 => Direction::West,
+                    3 => Direction::North,
+                    digit => return Err(digit),
+                };
+                Ok(Step {
+                    direction,
+                    count: (hex >> 4) as usize,
+                })
+            }
+        }
+    }
 }
 
 fn parse_instruction(inp: &str) -> IResult<&str, Instruction> {
     let (inp, instruction) = map(
         tuple((
             terminated(one_of("UDLR"), tag(" ")),
-            terminated(digit1, tag(" ")),
+            terminated(map_res(digit1, str::parse::<usize>), tag(" ")),
             delimited(tag("("), hex_color, tag(")")),
         )),
         |(direction, count, color)| {
@@ -135,32 +273,12 @@ fn parse_instruction(inp: &str) -> IResult<&str, Instruction> {
                 'R' => Direction::East,
                 _ => unreachable!(),
             };
-            Instruction::new(direction, count.parse::<usize>().unwrap(), color)
+            Instruction::new(direction, count, color)
         },
     )(inp)?;
     Ok((inp, instruction))
 }
 
-fn parse_true_instruction(inp: &str) -> IResult<&str, Instruction> {
-    let (inp, (_, _, hex)) = tuple((
-        terminated(one_of("UDLR"), tag(" ")),
-        terminated(digit1, tag(" ")),
-        delimited(tag("(#"), hex_digit1, tag(")")),
-    ))(inp)?;
-    // |(direction, count, hex)| {
-    let direction = match hex.chars().last().unwrap() {
-        '0' => Direction::East,
-        '1' => Direction::South,
-        '2' => Direction::West,
-        '3' => Direction::North,
-        _ => unreachable!(),
-    };
-
-    let count = usize::from_str_radix(&hex[..5], 16).unwrap();
-    let instruction = Instruction::new(direction, count, Color::default());
-    Ok((inp, instruction))
-}
-
 pub fn parse_instruction_set(inp: &str) -> IResult<&str, InstructionSet> {
     map(
         many1(terminated(parse_instruction, newline)),
@@ -168,13 +286,6 @@ pub fn parse_instruction_set(inp: &str) -> IResult<&str, InstructionSet> {
     )(inp)
 }
 
-pub fn parse_true_instruction_set(inp: &str) -> IResult<&str, InstructionSet> {
-    map(
-        many1(terminated(parse_true_instruction, newline)),
-        InstructionSet,
-    )(inp)
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -199,22 +310,60 @@ mod tests {
         let inp = include_str!("../../data/sample_input.txt");
         let instruction_set = parse_instruction_set(inp).unwrap().1;
         assert_eq!(instruction_set.0.len(), 14);
-        let outline = instruction_set.draw_polygon();
-        let perimiter = instruction_set.get_perimiter();
+        let plan = instruction_set.plan(Reading::Literal).unwrap();
+        let outline = plan.draw_polygon();
+        let perimiter = plan.get_perimiter();
         let actual = outline.get_area() + (perimiter / 2) + 1;
         assert_eq!(actual, 62);
     }
 
+    #[test_case("R 6 (#70c710)", Reading::Literal, Ok(Step{direction: Direction::East, count: 6}); "literal")]
+    #[test_case("R 6 (#70c710)", Reading::Hex, Ok(Step{direction: Direction::East, count: 461937}); "hex")]
+    #[test_case("U 2 (#caa173)", Reading::Hex, Ok(Step{direction: Direction::North, count: 829975}); "hex north")]
+    #[test_case("R 6 (#70c71a)", Reading::Hex, Err(0xa); "bad hex direction")]
+    fn test_step(inp: &str, reading: Reading, exp: Result<Step, u8>) {
+        let instruction = parse_instruction(inp).unwrap().1;
+        assert_eq!(instruction.step(reading), exp);
+    }
+
+    #[test_case(Reading::Literal, 62)]
+    #[test_case(Reading::Hex, 952408144115)]
+    fn test_lagoon_size(reading: Reading, exp: usize) {
+        let inp = include_str!("../../data/sample_input.txt");
+        let instruction_set = parse_instruction_set(inp).unwrap().1;
+        assert_eq!(instruction_set.plan(reading).unwrap().lagoon_size(), exp);
+    }
+
+    #[test]
+    fn test_counterclockwise_plan() {
+        let inp = "U 2 (#000000)\nL 2 (#000000)\nD 2 (#000000)\nR 2 (#000000)\n";
+        let instruction_set = parse_instruction_set(inp).unwrap().1;
+        assert_eq!(
+            instruction_set
+                .plan(Reading::Literal)
+                .unwrap()
+                .lagoon_size(),
+            9
+        );
+    }
+
+    #[test_case("R 2 (#000000)\nD 2 (#000000)\nL 2 (#000000)\n", PlanError::NotClosed { end: Loc::new(0, 2) }; "not closed")]
+    #[test_case("R 2 (#000000)\nD 0 (#000000)\nL 2 (#000000)\n", PlanError::ZeroLength { index: 1 }; "zero length")]
+    #[test_case("R 2 (#000000)\nL 2 (#000000)\n", PlanError::SelfIntersecting { index: 1, other: 0 }; "doubles back")]
+    #[test_case("R 2 (#000000)\nD 1 (#000000)\nL 1 (#000000)\nU 2 (#000000)\nL 1 (#000000)\nD 1 (#000000)\n", PlanError::SelfIntersecting { index: 3, other: 0 }; "crosses")]
+    #[test_case("R 2 (#000000)\nD 2 (#000000)\nL 1 (#000000)\nU 2 (#000000)\nL 1 (#000000)\n", PlanError::SelfIntersecting { index: 3, other: 0 }; "touches")]
+    fn test_invalid_plan(inp: &str, exp: PlanError) {
+        let instruction_set = parse_instruction_set(inp).unwrap().1;
+        assert_eq!(instruction_set.plan(Reading::Literal).err(), Some(exp));
+    }
+
     #[test]
-    fn test_parse_true_instruction() {
-        let inp = "R 6 (#70c710)";
-        let actual = parse_true_instruction(inp);
+    fn test_bad_hex_direction_index() {
+        let inp = "R 1 (#000010)\nD 1 (#000014)\n";
+        let instruction_set = parse_instruction_set(inp).unwrap().1;
         assert_eq!(
-            actual,
-            Ok((
-                "",
-                Instruction::new(Direction::East, 461937, Color::default())
-            ))
+            instruction_set.plan(Reading::Hex).err(),
+            Some(PlanError::BadHexDirection { index: 1, digit: 4 })
         );
     }
 }
@@ -1,9 +1,14 @@
 mod shared;
-use shared::{parse_workflows_and_ratings, Operation, Workflow, Rating};
+use aoc_utils::dot::ToDot;
+use shared::{parse_workflows_and_ratings, Operation, Workflow, WorkflowGraph, Rating};
 
 fn main() {
     let inp = include_str!("../data/sample_input.txt");
     let (_, (workflows, ratings)) = parse_workflows_and_ratings(inp).unwrap();
+    if std::env::args().any(|a| a == "--dot") {
+        print!("{}", WorkflowGraph(&workflows).to_dot());
+        return;
+    }
     let sum = rate_all_workflows(workflows);
     println!("{}", sum);
 
@@ -1,3 +1,4 @@
+use aoc_utils::dot::{DotGraph, ToDot};
 use nom::{
     bytes::complete::tag,
     character::complete::{alpha1, char as nomchar, digit1, newline, one_of},
@@ -6,6 +7,7 @@ use nom::{
     sequence::{delimited, preceded, terminated, tuple, separated_pair},
     IResult,
 };
+use std::fmt::Display;
 
 #[derive(Debug, PartialEq)]
 pub enum Operation<'a> {
@@ -22,6 +24,15 @@ impl<'a> From<&'a str> for Operation<'a> {
         }
     }
 }
+impl Operation<'_> {
+    fn target(&self) -> &str {
+        match self {
+            Operation::Accepted => "A",
+            Operation::Rejected => "R",
+            Operation::Run(workflow_name) => workflow_name,
+        }
+    }
+}
 #[derive(Debug, PartialEq)]
 pub struct Rating {
     x: usize,
@@ -104,6 +115,16 @@ impl From<char> for Category {
         }
     }
 }
+impl Display for Category {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Category::X => "x",
+            Category::M => "m",
+            Category::A => "a",
+            Category::S => "s",
+        })
+    }
+}
 #[derive(Debug, PartialEq)]
 enum Comparison {
     GreaterThan,
@@ -119,6 +140,14 @@ impl From<char> for Comparison {
         }
     }
 }
+impl Display for Comparison {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Comparison::GreaterThan => ">",
+            Comparison::LessThan => "<",
+        })
+    }
+}
 #[derive(Debug, PartialEq)]
 struct Rule<'a> {
     category: Category,
@@ -160,6 +189,29 @@ impl<'a> Workflow<'a> {
     }
 }
 
+/// The workflows as a graph, one node per workflow plus the accept and reject outcomes
+pub struct WorkflowGraph<'w, 'a>(pub &'w [Workflow<'a>]);
+
+/// Rule conditions label their edges, the fallthrough edge is left unlabeled
+impl ToDot for WorkflowGraph<'_, '_> {
+    fn to_dot(&self) -> DotGraph {
+        let mut graph = DotGraph::new("workflows");
+        for workflow in self.0 {
+            graph.add_node(workflow.name, "box");
+        }
+        graph.add_node("A", "doublecircle");
+        graph.add_node("R", "octagon");
+        for workflow in self.0 {
+            for rule in &workflow.rules {
+                let condition = format!("{}{}{}", rule.category, rule.comparison, rule.value);
+                graph.add_edge(workflow.name, rule.operation.target(), Some(&condition));
+            }
+            graph.add_edge(workflow.name, workflow.fallthrough_op.target(), None);
+        }
+        graph
+    }
+}
+
 fn parse_workflow(inp: &str) -> IResult<&str, Workflow> {
     map(
         tuple((
@@ -268,4 +320,24 @@ mod tests {
         assert_eq!(workflows.len(), 11);
         assert_eq!(ratings.len(), 5);
     }
+
+    #[test]
+    fn test_workflows_to_dot() {
+        let inp = "in{s<1351:px,qqz}\npx{a<2006:qkq,m>2090:A,rfg}\n";
+        let workflows = parse_workflow_set(inp).unwrap().1;
+        let expected = "\
+digraph \"workflows\" {
+    \"in\" [shape=box];
+    \"px\" [shape=box];
+    \"A\" [shape=doublecircle];
+    \"R\" [shape=octagon];
+    \"in\" -> \"px\" [label=\"s<1351\"];
+    \"in\" -> \"qqz\";
+    \"px\" -> \"qkq\" [label=\"a<2006\"];
+    \"px\" -> \"A\" [label=\"m>2090\"];
+    \"px\" -> \"rfg\";
+}
+";
+        assert_eq!(WorkflowGraph(&workflows).to_dot().to_string(), expected);
+    }
 }
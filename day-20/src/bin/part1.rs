@@ -1,5 +1,31 @@
 mod shared;
+use aoc_utils::dot::ToDot;
+use shared::{parse_network, Network};
 
 fn main() {
+    let inp = include_str!("../data/puzzle_input.txt");
+    let mut network = parse_network(inp).unwrap().1;
+    if std::env::args().any(|a| a == "--dot") {
+        print!("{}", network.to_dot());
+        return;
+    }
+    println!("{}", multiply_pulses(&mut network, 1000));
+}
+
+fn multiply_pulses(network: &mut Network, presses: usize) -> usize {
+    network.press_button_times(presses);
+    network.low_pulses() * network.high_pulses()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
 
+    #[test_case(include_str!("../data/sample_input.txt"), 32000000; "Sample 1")]
+    #[test_case(include_str!("../data/sample_input2.txt"), 11687500; "Sample 2")]
+    fn test_multiply_pulses(inp: &str, exp: usize) {
+        let mut network = parse_network(inp).unwrap().1;
+        assert_eq!(multiply_pulses(&mut network, 1000), exp);
+    }
 }
@@ -1,5 +1,42 @@
 mod shared;
+use aoc_utils::dot::ToDot;
+use shared::parse_network;
 
 fn main() {
+    let inp = include_str!("../data/puzzle_input.txt");
+    let mut network = parse_network(inp).unwrap().1;
+    if std::env::args().any(|a| a == "--dot") {
+        print!("{}", network.to_dot());
+        return;
+    }
+    let target = std::env::args().nth(1).unwrap_or("rx".to_string());
+    println!("{}", network.presses_until_low_pulse(&target).unwrap());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn brute_force_presses(inp: &str, target: &str) -> usize {
+        let mut network = parse_network(inp).unwrap().1;
+        let mut presses = 0;
+        loop {
+            presses += 1;
+            if network
+                .press_button_events()
+                .any(|p| p.destination() == target && p.signal() == shared::Signal::Low)
+            {
+                return presses;
+            }
+        }
+    }
 
+    #[test]
+    fn test_presses_until_low_pulse() {
+        let inp = include_str!("../data/sample_input3.txt");
+        let mut network = parse_network(inp).unwrap().1;
+        let actual = network.presses_until_low_pulse("rx");
+        assert_eq!(actual, Ok(15));
+        assert_eq!(actual, Ok(brute_force_presses(inp, "rx")));
+    }
 }
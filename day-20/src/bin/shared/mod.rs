@@ -1,3 +1,5 @@
+use aoc_utils::cycle::detect_cycle;
+use aoc_utils::dot::{DotGraph, ToDot};
 use nom::{
     branch::alt,
     bytes::complete::tag,
@@ -7,33 +9,100 @@ use nom::{
     sequence::{delimited, terminated, tuple},
     IResult,
 };
-use std::fmt::Debug;
-use std::collections::VecDeque;
+use num::Integer;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::fmt::{Debug, Display};
+
+const BUTTON: &str = "button";
+const BROADCASTER: &str = "broadcaster";
+const MAX_PERIOD_PRESSES: usize = 100_000;
 
 #[derive(Debug, PartialEq)]
-enum Signal {
+pub enum PeriodError {
+    NoFeeder(String),
+    MultipleFeeders(String),
+    FeederNotConjunction(String),
+    OverlappingSubNetworks(String, String),
+    NoPeriodFound(String),
+    IrregularPeriod(String),
+}
+
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum Signal {
     High,
     Low,
 }
 
-#[derive(Debug, PartialEq)]
-enum ModuleType {
+impl Display for Signal {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Signal::High => "high",
+            Signal::Low => "low",
+        })
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Pulse {
+    source: String,
+    signal: Signal,
+    destination: String,
+}
+
+impl Pulse {
+    fn new(source: &str, signal: Signal, destination: &str) -> Self {
+        Self {
+            source: source.to_string(),
+            signal,
+            destination: destination.to_string(),
+        }
+    }
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+    pub fn signal(&self) -> Signal {
+        self.signal
+    }
+    pub fn destination(&self) -> &str {
+        &self.destination
+    }
+}
+
+/// Formats as the puzzle does, e.g. "broadcaster -low-> a"
+impl Display for Pulse {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} -{}-> {}", self.source, self.signal, self.destination)
+    }
+}
+
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum ModuleType {
     FlipFlop,
     Conjunction,
     Broadcast,
     Button,
 }
 
-#[derive(Debug, PartialEq)]
+impl ModuleType {
+    fn dot_shape(&self) -> &str {
+        match self {
+            ModuleType::FlipFlop => "box",
+            ModuleType::Conjunction => "diamond",
+            ModuleType::Broadcast => "doublecircle",
+            ModuleType::Button => "circle",
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Copy, Clone)]
 enum FlipFlopState {
     On,
     Off,
 }
 #[derive(Debug, PartialEq)]
-struct FlipFlop {
+pub struct FlipFlop {
     name: String,
     current_state: FlipFlopState,
-    commands: VecDeque<Signal>,
     destination_modules: Vec<String>,
 }
 
@@ -42,28 +111,28 @@ impl FlipFlop {
         Self {
             name: name.to_string(),
             current_state: FlipFlopState::Off,
-            commands: VecDeque::new(),
             destination_modules: destination_modules.iter().map(|s| s.to_string()).collect(),
         }
     }
 }
-#[derive(Default, Debug, PartialEq)]
-struct Broadcaster {
+#[derive(Debug, PartialEq)]
+pub struct Broadcaster {
+    name: String,
     destination_modules: Vec<String>,
 }
 
 impl Broadcaster {
     fn new(destination_modules: Vec<&str>) -> Self {
         Self {
+            name: BROADCASTER.to_string(),
             destination_modules: destination_modules.iter().map(|s| s.to_string()).collect(),
         }
     }
 }
 #[derive(Debug, PartialEq)]
-struct Conjunction {
+pub struct Conjunction {
     name: String,
-    commands: VecDeque<Signal>,
-    last_command: Option<Signal>,
+    last_commands: BTreeMap<String, Signal>,
     destination_modules: Vec<String>,
 }
 
@@ -71,96 +140,459 @@ impl Conjunction {
     fn new(name: &str, destination_modules: Vec<&str>) -> Self {
         Self {
             name: name.to_string(),
-            commands: VecDeque::new(),
-            last_command: None,
+            last_commands: BTreeMap::new(),
             destination_modules: destination_modules.iter().map(|s| s.to_string()).collect(),
         }
     }
 }
 
-trait Module {
-    fn queue_command(&mut self, signal: Signal) {
-        self.commands().push_back(signal);
+pub trait Module: Debug {
+    fn name(&self) -> &str;
+    fn module_type(&self) -> ModuleType;
+    fn destination_modules(&self) -> &[String];
+    /// Called once per module wired into self, before any signal is sent
+    fn connect_input(&mut self, _source: &str) {}
+    /// Returns the signal to send to every destination, if any
+    fn handle_command(&mut self, source: &str, signal: Signal) -> Option<Signal>;
+    fn state(&self) -> Vec<Signal> {
+        vec![]
     }
-    fn commands(&mut self) -> VecDeque<Signal>;
+    /// Accepts a slice previously returned by `state`
+    fn restore_state(&mut self, _state: &[Signal]) {}
 }
 impl Module for FlipFlop {
-    fn commands(&mut self) -> VecDeque<Signal> {
-        self.commands
+    fn name(&self) -> &str {
+        &self.name
+    }
+    fn module_type(&self) -> ModuleType {
+        ModuleType::FlipFlop
+    }
+    fn destination_modules(&self) -> &[String] {
+        &self.destination_modules
+    }
+    fn handle_command(&mut self, _source: &str, signal: Signal) -> Option<Signal> {
+        match signal {
+            Signal::High => None,
+            Signal::Low => match self.current_state {
+                FlipFlopState::Off => {
+                    self.current_state = FlipFlopState::On;
+                    Some(Signal::High)
+                }
+                FlipFlopState::On => {
+                    self.current_state = FlipFlopState::Off;
+                    Some(Signal::Low)
+                }
+            },
+        }
+    }
+    fn state(&self) -> Vec<Signal> {
+        match self.current_state {
+            FlipFlopState::On => vec![Signal::High],
+            FlipFlopState::Off => vec![Signal::Low],
+        }
+    }
+    fn restore_state(&mut self, state: &[Signal]) {
+        self.current_state = match state {
+            [Signal::High] => FlipFlopState::On,
+            _ => FlipFlopState::Off,
+        }
     }
 }
 impl Module for Conjunction {
-    fn commands(&mut self) -> VecDeque<Signal> {
-        self.commands
+    fn name(&self) -> &str {
+        &self.name
+    }
+    fn module_type(&self) -> ModuleType {
+        ModuleType::Conjunction
+    }
+    fn destination_modules(&self) -> &[String] {
+        &self.destination_modules
+    }
+    fn connect_input(&mut self, source: &str) {
+        self.last_commands.insert(source.to_string(), Signal::Low);
+    }
+    fn handle_command(&mut self, source: &str, signal: Signal) -> Option<Signal> {
+        self.last_commands.insert(source.to_string(), signal);
+        match self.last_commands.values().all(|s| *s == Signal::High) {
+            true => Some(Signal::Low),
+            false => Some(Signal::High),
+        }
+    }
+    fn state(&self) -> Vec<Signal> {
+        self.last_commands.values().copied().collect()
+    }
+    fn restore_state(&mut self, state: &[Signal]) {
+        for (last_command, signal) in self.last_commands.values_mut().zip(state) {
+            *last_command = *signal;
+        }
     }
 }
 impl Module for Broadcaster {
-    fn commands(&mut self) -> VecDeque<Signal> {
-        todo!()
-    }
-}
-// #[derive(Debug, PartialEq)]
-// struct Module<'a> {
-//     module_type: ModuleType,
-//     name: &'a str,
-//     current_state: Signal,
-//     commands: VecDeque<Signal>,
-//     destination_modules: Vec<&'a str>,
-// }
-// impl<'a> Module<'a> {
-//     fn new(module_type: ModuleType, name: &'a str, destination_modules: Vec<&'a str>) -> Self {
-//         let current_state = Signal::Low;
-//         let commands = VecDeque::new();
-//         Self {
-//             module_type,
-//             name,
-//             current_state,
-//             commands,
-//             destination_modules,
-//         }
-//     }
-
-//     fn queue_command(&mut self, signal: Signal) {
-//         self.commands.push_back(signal);
-//     }
-
-//     fn handle_next_command(&mut self, mut modules: Vec<Module>) {
-//         match self.module_type {
-//             ModuleType::Broadcast => {
-//                 self.destination_modules.iter().for_each(|name| {
-//                     let target_module = modules.iter_mut().find(|m|&m.name == name).unwrap();
-//                     target_module.queue_command(Signal::Low);
-//                 })
-//             },
-//             ModuleType::FlipFlop => {
-
-//             },
-//             ModuleType::Conjunction => todo!(),
-//             ModuleType::Button => todo!(),
-//         }
-//         match self.current_state {
-//             Signal::High => match self.commands.pop_front().unwrap() {
-//                 Signal::High => todo!(),
-//                 Signal::Low => todo!(),
-//             },
-//             Signal::Low => match self.commands.pop_front().unwrap() {
-//                 Signal::High => todo!(),
-//                 Signal::Low => todo!(),
-//             },
-//         }
-//     }
-// }
-
-fn parse_broadcaster(inp: &str) -> IResult<&str, impl Module + Debug + PartialEq> {
+    fn name(&self) -> &str {
+        &self.name
+    }
+    fn module_type(&self) -> ModuleType {
+        ModuleType::Broadcast
+    }
+    fn destination_modules(&self) -> &[String] {
+        &self.destination_modules
+    }
+    fn handle_command(&mut self, _source: &str, signal: Signal) -> Option<Signal> {
+        Some(signal)
+    }
+}
+
+/// Every module's state packed into bits, in module name order
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+pub struct NetworkSnapshot(Vec<u64>);
+
+#[derive(Debug)]
+pub struct Network {
+    modules: HashMap<String, Box<dyn Module>>,
+    low_pulses: usize,
+    high_pulses: usize,
+}
+
+impl Network {
+    pub fn new(modules: Vec<Box<dyn Module>>) -> Self {
+        let connections: Vec<(String, String)> = modules
+            .iter()
+            .flat_map(|m| {
+                m.destination_modules()
+                    .iter()
+                    .map(|d| (m.name().to_string(), d.to_string()))
+            })
+            .collect();
+        let mut modules: HashMap<String, Box<dyn Module>> = modules
+            .into_iter()
+            .map(|m| (m.name().to_string(), m))
+            .collect();
+        for (source, destination) in connections {
+            if let Some(module) = modules.get_mut(&destination) {
+                module.connect_input(&source);
+            }
+        }
+        Self {
+            modules,
+            low_pulses: 0,
+            high_pulses: 0,
+        }
+    }
+
+    pub fn press_button(&mut self) -> PressSummary {
+        let mut events = self.press_button_events();
+        events.by_ref().for_each(drop);
+        events.summary()
+    }
+
+    pub fn press_button_events(&mut self) -> PulseEvents<'_> {
+        PulseEvents::new(self)
+    }
+
+    fn inputs_of(&self, name: &str) -> Vec<&str> {
+        let mut inputs: Vec<&str> = self
+            .modules
+            .values()
+            .filter(|m| m.destination_modules().iter().any(|d| d == name))
+            .map(|m| m.name())
+            .collect();
+        inputs.sort();
+        inputs
+    }
+
+    /// Every module upstream of `name`, stopping at the broadcaster
+    fn sub_network<'a>(&'a self, name: &'a str) -> HashSet<&'a str> {
+        let mut sub_network = HashSet::new();
+        let mut to_visit = vec![name];
+        while let Some(current) = to_visit.pop() {
+            if current == BROADCASTER || !sub_network.insert(current) {
+                continue;
+            }
+            to_visit.extend(self.inputs_of(current));
+        }
+        sub_network
+    }
+
+    /// Presses needed before `target` receives a low pulse, counted from the freshly parsed
+    /// state whatever has been pressed so far. The network's state and pulse totals are put
+    /// back before returning.
+    ///
+    /// `target` must be fed by a single conjunction whose inputs are independent
+    /// sub-networks that each send it a high pulse on a fixed period starting at press 0.
+    #[allow(dead_code)]
+    pub fn presses_until_low_pulse(&mut self, target: &str) -> Result<usize, PeriodError> {
+        let saved = self.snapshot();
+        let (low_pulses, high_pulses) = (self.low_pulses, self.high_pulses);
+        // Every flip-flop starts off and every conjunction input starts low
+        self.restore(&NetworkSnapshot(vec![0; saved.0.len()]));
+        let presses = self.presses_until_low_pulse_from_here(target);
+        self.restore(&saved);
+        self.low_pulses = low_pulses;
+        self.high_pulses = high_pulses;
+        presses
+    }
+
+    fn presses_until_low_pulse_from_here(&mut self, target: &str) -> Result<usize, PeriodError> {
+        let feeder = match self.inputs_of(target)[..] {
+            [] => return Err(PeriodError::NoFeeder(target.to_string())),
+            [feeder] => feeder.to_string(),
+            _ => return Err(PeriodError::MultipleFeeders(target.to_string())),
+        };
+        if self.modules[&feeder].module_type() != ModuleType::Conjunction {
+            return Err(PeriodError::FeederNotConjunction(feeder));
+        }
+        let sources: Vec<String> = self
+            .inputs_of(&feeder)
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let sub_networks: Vec<HashSet<&str>> =
+            sources.iter().map(|s| self.sub_network(s)).collect();
+        for (i, j) in (0..sources.len()).flat_map(|i| (i + 1..sources.len()).map(move |j| (i, j))) {
+            if !sub_networks[i].is_disjoint(&sub_networks[j]) {
+                return Err(PeriodError::OverlappingSubNetworks(
+                    sources[i].clone(),
+                    sources[j].clone(),
+                ));
+            }
+        }
+
+        let mut high_presses: HashMap<&str, Vec<usize>> = HashMap::new();
+        for press in 1..=MAX_PERIOD_PRESSES {
+            for pulse in self.press_button_events() {
+                if pulse.destination() == feeder && pulse.signal() == Signal::High {
+                    if let Some(source) = sources.iter().find(|s| *s == pulse.source()) {
+                        high_presses.entry(source).or_default().push(press);
+                    }
+                }
+            }
+            if sources
+                .iter()
+                .all(|s| high_presses.get(s.as_str()).is_some_and(|p| p.len() >= 2))
+            {
+                break;
+            }
+        }
+
+        let mut presses = 1;
+        for source in &sources {
+            let period = match high_presses.get(source.as_str()).map(|p| &p[..]) {
+                Some([first, second, ..]) if *second == first * 2 => *first,
+                Some([_, _, ..]) => return Err(PeriodError::IrregularPeriod(source.clone())),
+                _ => return Err(PeriodError::NoPeriodFound(source.clone())),
+            };
+            presses = presses.lcm(&period);
+        }
+        Ok(presses)
+    }
+
+    fn sorted_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.modules.keys().cloned().collect();
+        names.sort();
+        names
+    }
+
+    pub fn snapshot(&self) -> NetworkSnapshot {
+        let mut bits = vec![];
+        let mut bit = 0;
+        for name in self.sorted_names() {
+            for signal in self.modules[&name].state() {
+                if bit % 64 == 0 {
+                    bits.push(0);
+                }
+                if signal == Signal::High {
+                    bits[bit / 64] |= 1 << (bit % 64);
+                }
+                bit += 1;
+            }
+        }
+        NetworkSnapshot(bits)
+    }
+
+    /// Pulse totals are left untouched
+    pub fn restore(&mut self, snapshot: &NetworkSnapshot) {
+        let mut bit = 0;
+        for name in self.sorted_names() {
+            let module = self.modules.get_mut(&name).unwrap();
+            let state: Vec<Signal> = (bit..bit + module.state().len())
+                .map(|b| match snapshot.0[b / 64] >> (b % 64) & 1 {
+                    1 => Signal::High,
+                    _ => Signal::Low,
+                })
+                .collect();
+            bit += state.len();
+            module.restore_state(&state);
+        }
+    }
+
+    /// Same end state and pulse totals as `press_button_times`, skipping ahead once the state repeats
+    #[allow(dead_code)]
+    pub fn fast_forward(&mut self, presses: usize) {
+        let mut history = vec![];
+        let cycle = detect_cycle(
+            || {
+                let snapshot = self.snapshot();
+                history.push((snapshot.clone(), self.low_pulses, self.high_pulses));
+                if history.len() <= presses {
+                    self.press_button();
+                }
+                snapshot
+            },
+            presses + 1,
+        );
+        let Some(cycle) = cycle else {
+            return;
+        };
+        let step = cycle.equivalent_step(presses);
+        let repeats = (presses - step) / cycle.length();
+        let (_, cycle_start_low, cycle_start_high) = history[cycle.start()];
+        let (_, cycle_end_low, cycle_end_high) = history[cycle.start() + cycle.length()];
+        let (snapshot, low_pulses, high_pulses) = &history[step];
+        self.restore(snapshot);
+        self.low_pulses = low_pulses + repeats * (cycle_end_low - cycle_start_low);
+        self.high_pulses = high_pulses + repeats * (cycle_end_high - cycle_start_high);
+    }
+
+    #[allow(dead_code)]
+    pub fn press_button_times(&mut self, presses: usize) {
+        for _ in 0..presses {
+            self.press_button();
+        }
+    }
+
+    #[allow(dead_code)]
+    pub fn low_pulses(&self) -> usize {
+        self.low_pulses
+    }
+
+    #[allow(dead_code)]
+    pub fn high_pulses(&self) -> usize {
+        self.high_pulses
+    }
+}
+
+/// Modules that only appear as destinations, such as `output` or `rx`, are drawn as plain text
+impl ToDot for Network {
+    fn to_dot(&self) -> DotGraph {
+        let mut graph = DotGraph::new("network");
+        graph.add_node(BUTTON, ModuleType::Button.dot_shape());
+        graph.add_edge(BUTTON, BROADCASTER, None);
+        let mut names: Vec<&String> = self.modules.keys().collect();
+        names.sort();
+        for name in names {
+            let module = &self.modules[name];
+            graph.add_node(name, module.module_type().dot_shape());
+            for destination in module.destination_modules() {
+                if !self.modules.contains_key(destination) {
+                    graph.add_node(destination, "plaintext");
+                }
+                graph.add_edge(name, destination, None);
+            }
+        }
+        graph
+    }
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct PressSummary {
+    low_pulses: usize,
+    high_pulses: usize,
+    changed_modules: Vec<String>,
+}
+
+#[allow(dead_code)]
+impl PressSummary {
+    pub fn low_pulses(&self) -> usize {
+        self.low_pulses
+    }
+    pub fn high_pulses(&self) -> usize {
+        self.high_pulses
+    }
+    /// Modules whose state at the end of the press differs from the start, sorted by name
+    pub fn changed_modules(&self) -> &[String] {
+        &self.changed_modules
+    }
+}
+
+/// The pulses of a single button press, yielded in the order they are processed
+pub struct PulseEvents<'a> {
+    network: &'a mut Network,
+    queue: VecDeque<Pulse>,
+    low_pulses: usize,
+    high_pulses: usize,
+    initial_states: BTreeMap<String, Vec<Signal>>,
+}
+
+impl<'a> PulseEvents<'a> {
+    fn new(network: &'a mut Network) -> Self {
+        Self {
+            network,
+            queue: VecDeque::from([Pulse::new(BUTTON, Signal::Low, BROADCASTER)]),
+            low_pulses: 0,
+            high_pulses: 0,
+            initial_states: BTreeMap::new(),
+        }
+    }
+
+    pub fn summary(&self) -> PressSummary {
+        let changed_modules = self
+            .initial_states
+            .iter()
+            .filter(|(name, state)| self.network.modules[*name].state() != **state)
+            .map(|(name, _)| name.to_string())
+            .collect();
+        PressSummary {
+            low_pulses: self.low_pulses,
+            high_pulses: self.high_pulses,
+            changed_modules,
+        }
+    }
+}
+
+impl Iterator for PulseEvents<'_> {
+    type Item = Pulse;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let pulse = self.queue.pop_front()?;
+        match pulse.signal {
+            Signal::High => {
+                self.high_pulses += 1;
+                self.network.high_pulses += 1;
+            }
+            Signal::Low => {
+                self.low_pulses += 1;
+                self.network.low_pulses += 1;
+            }
+        }
+        // Untyped modules such as `output` receive pulses but never send any
+        if let Some(module) = self.network.modules.get_mut(&pulse.destination) {
+            self.initial_states
+                .entry(pulse.destination.clone())
+                .or_insert_with(|| module.state());
+            if let Some(next_signal) = module.handle_command(&pulse.source, pulse.signal) {
+                for next_destination in module.destination_modules() {
+                    self.queue.push_back(Pulse::new(
+                        &pulse.destination,
+                        next_signal,
+                        next_destination,
+                    ));
+                }
+            }
+        }
+        Some(pulse)
+    }
+}
+
+fn parse_broadcaster(inp: &str) -> IResult<&str, Broadcaster> {
     map(
         tuple((
-            terminated(tag("broadcaster"), tag(" -> ")),
+            terminated(tag(BROADCASTER), tag(" -> ")),
             many1(terminated(alpha1, opt(tag(", ")))),
         )),
-        |(name, destination_modules)| Broadcaster::default(),
+        |(_, destination_modules)| Broadcaster::new(destination_modules),
     )(inp)
 }
-fn parse_flip_flop(inp: &str) -> IResult<&str, impl Module + Debug + PartialEq> {
+fn parse_flip_flop(inp: &str) -> IResult<&str, FlipFlop> {
     map(
         tuple((
             delimited(nomchar('%'), alpha1, tag(" -> ")),
@@ -169,7 +601,7 @@ fn parse_flip_flop(inp: &str) -> IResult<&str, impl Module + Debug + PartialEq>
         |(name, destination_modules): (&str, Vec<&str>)| FlipFlop::new(name, destination_modules),
     )(inp)
 }
-fn parse_conjunction(inp: &str) -> IResult<&str, impl Module + Debug + PartialEq> {
+fn parse_conjunction(inp: &str) -> IResult<&str, Conjunction> {
     map(
         tuple((
             delimited(nomchar('&'), alpha1, tag(" -> ")),
@@ -178,13 +610,21 @@ fn parse_conjunction(inp: &str) -> IResult<&str, impl Module + Debug + PartialEq
         |(name, destination_modules)| Conjunction::new(name, destination_modules),
     )(inp)
 }
-pub fn parse_configuration(inp: &str) -> IResult<&str, Vec<impl Module>> {
+pub fn parse_configuration(inp: &str) -> IResult<&str, Vec<Box<dyn Module>>> {
     many1(terminated(
-        alt((parse_broadcaster, parse_flip_flop, parse_conjunction)),
+        alt((
+            map(parse_broadcaster, |m| Box::new(m) as Box<dyn Module>),
+            map(parse_flip_flop, |m| Box::new(m) as Box<dyn Module>),
+            map(parse_conjunction, |m| Box::new(m) as Box<dyn Module>),
+        )),
         newline,
     ))(inp)
 }
 
+pub fn parse_network(inp: &str) -> IResult<&str, Network> {
+    map(parse_configuration, Network::new)(inp)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -218,4 +658,185 @@ mod tests {
         let actual = parse_configuration(inp).unwrap().1;
         assert_eq!(actual.len(), 5);
     }
+
+    #[test_case(Signal::Low, Some(Signal::High), FlipFlopState::On; "Low turns on")]
+    #[test_case(Signal::High, None, FlipFlopState::Off; "High is ignored")]
+    fn test_flip_flop_handle_command(
+        signal: Signal,
+        exp: Option<Signal>,
+        exp_state: FlipFlopState,
+    ) {
+        let mut flip_flop = FlipFlop::new("a", vec!["b"]);
+        let actual = flip_flop.handle_command(BROADCASTER, signal);
+        assert_eq!(actual, exp);
+        assert_eq!(flip_flop.current_state, exp_state);
+    }
+
+    #[test]
+    fn test_conjunction_remembers_inputs() {
+        let mut conjunction = Conjunction::new("inv", vec!["a"]);
+        conjunction.connect_input("b");
+        conjunction.connect_input("c");
+        assert_eq!(
+            conjunction.handle_command("b", Signal::High),
+            Some(Signal::High)
+        );
+        assert_eq!(
+            conjunction.handle_command("c", Signal::High),
+            Some(Signal::Low)
+        );
+        assert_eq!(
+            conjunction.handle_command("b", Signal::Low),
+            Some(Signal::High)
+        );
+    }
+
+    #[test_case(include_str!("../../data/sample_input.txt"), 1, (8, 4); "Sample 1")]
+    #[test_case(include_str!("../../data/sample_input2.txt"), 1, (4, 4); "Sample 2 first press")]
+    #[test_case(include_str!("../../data/sample_input2.txt"), 4, (17, 11); "Sample 2 cycle")]
+    fn test_press_button_times(inp: &str, presses: usize, (exp_low, exp_high): (usize, usize)) {
+        let mut network = parse_network(inp).unwrap().1;
+        network.press_button_times(presses);
+        assert_eq!(network.low_pulses(), exp_low);
+        assert_eq!(network.high_pulses(), exp_high);
+    }
+
+    #[test_case(include_str!("../../data/sample_input.txt"), "\
+button -low-> broadcaster
+broadcaster -low-> a
+broadcaster -low-> b
+broadcaster -low-> c
+a -high-> b
+b -high-> c
+c -high-> inv
+inv -low-> a
+a -low-> b
+b -low-> c
+c -low-> inv
+inv -high-> a"; "Sample 1")]
+    #[test_case(include_str!("../../data/sample_input2.txt"), "\
+button -low-> broadcaster
+broadcaster -low-> a
+a -high-> inv
+a -high-> con
+inv -low-> b
+con -high-> output
+b -high-> con
+con -low-> output"; "Sample 2")]
+    fn test_press_button_events(inp: &str, exp: &str) {
+        let mut network = parse_network(inp).unwrap().1;
+        let actual = network
+            .press_button_events()
+            .map(|p| p.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(actual, exp.lines().collect::<Vec<_>>());
+    }
+
+    #[test_case(include_str!("../../data/sample_input.txt"), 1, (8, 4, vec![]); "Sample 1")]
+    #[test_case(include_str!("../../data/sample_input2.txt"), 1, (4, 4, vec!["a", "b", "con", "inv"]); "Sample 2 press 1")]
+    #[test_case(include_str!("../../data/sample_input2.txt"), 2, (4, 2, vec!["a", "con", "inv"]); "Sample 2 press 2")]
+    fn test_press_button(
+        inp: &str,
+        presses: usize,
+        (exp_low, exp_high, exp_changed): (usize, usize, Vec<&str>),
+    ) {
+        let mut network = parse_network(inp).unwrap().1;
+        network.press_button_times(presses - 1);
+        let summary = network.press_button();
+        assert_eq!(summary.low_pulses(), exp_low);
+        assert_eq!(summary.high_pulses(), exp_high);
+        assert_eq!(summary.changed_modules(), exp_changed);
+    }
+
+    #[test]
+    fn test_to_dot() {
+        let inp = include_str!("../../data/sample_input2.txt");
+        let network = parse_network(inp).unwrap().1;
+        let expected = "\
+digraph \"network\" {
+    \"button\" [shape=circle];
+    \"a\" [shape=box];
+    \"b\" [shape=box];
+    \"broadcaster\" [shape=doublecircle];
+    \"con\" [shape=diamond];
+    \"output\" [shape=plaintext];
+    \"inv\" [shape=diamond];
+    \"button\" -> \"broadcaster\";
+    \"a\" -> \"inv\";
+    \"a\" -> \"con\";
+    \"b\" -> \"con\";
+    \"broadcaster\" -> \"a\";
+    \"con\" -> \"output\";
+    \"inv\" -> \"b\";
+}
+";
+        assert_eq!(network.to_dot().to_string(), expected);
+    }
+
+    #[test_case(include_str!("../../data/sample_input.txt"), "missing", PeriodError::NoFeeder("missing".to_string()); "No feeder")]
+    #[test_case(include_str!("../../data/sample_input.txt"), "b", PeriodError::MultipleFeeders("b".to_string()); "Multiple feeders")]
+    #[test_case(include_str!("../../data/sample_input.txt"), "inv", PeriodError::FeederNotConjunction("c".to_string()); "Flip-flop feeder")]
+    #[test_case(include_str!("../../data/sample_input2.txt"), "output", PeriodError::OverlappingSubNetworks("a".to_string(), "b".to_string()); "Overlapping")]
+    #[test_case(&include_str!("../../data/sample_input3.txt").replace("&cb -> na, nb, ib", "&cb -> na, ib"), "rx", PeriodError::IrregularPeriod("ib".to_string()); "Irregular")]
+    fn test_presses_until_low_pulse_errors(inp: &str, target: &str, exp: PeriodError) {
+        let mut network = parse_network(inp).unwrap().1;
+        assert_eq!(network.presses_until_low_pulse(target), Err(exp));
+    }
+
+    #[test]
+    fn test_presses_until_low_pulse_keeps_state() {
+        let inp = include_str!("../../data/sample_input3.txt");
+        let mut network = parse_network(inp).unwrap().1;
+        network.press_button_times(4);
+        let before = (
+            network.snapshot(),
+            network.low_pulses(),
+            network.high_pulses(),
+        );
+        assert_eq!(network.presses_until_low_pulse("rx"), Ok(15));
+        let after = (
+            network.snapshot(),
+            network.low_pulses(),
+            network.high_pulses(),
+        );
+        assert_eq!(after, before);
+    }
+
+    #[test]
+    fn test_snapshot_restore() {
+        let inp = include_str!("../../data/sample_input2.txt");
+        let mut network = parse_network(inp).unwrap().1;
+        let initial = network.snapshot();
+        network.press_button();
+        let after_one = network.snapshot();
+        assert_ne!(initial, after_one);
+        network.press_button_times(2);
+        network.restore(&after_one);
+        assert_eq!(network.snapshot(), after_one);
+        network.press_button_times(3);
+        assert_eq!(network.snapshot(), initial);
+    }
+
+    #[test_case(include_str!("../../data/sample_input.txt"), 1000; "Sample 1")]
+    #[test_case(include_str!("../../data/sample_input2.txt"), 1; "Sample 2 before cycle")]
+    #[test_case(include_str!("../../data/sample_input2.txt"), 1001; "Sample 2")]
+    #[test_case(include_str!("../../data/sample_input3.txt"), 1000; "Sample 3")]
+    fn test_fast_forward(inp: &str, presses: usize) {
+        let mut expected = parse_network(inp).unwrap().1;
+        expected.press_button_times(presses);
+        let mut actual = parse_network(inp).unwrap().1;
+        actual.fast_forward(presses);
+        assert_eq!(actual.snapshot(), expected.snapshot());
+        assert_eq!(actual.low_pulses(), expected.low_pulses());
+        assert_eq!(actual.high_pulses(), expected.high_pulses());
+    }
+
+    #[test]
+    fn test_fast_forward_billion() {
+        let inp = include_str!("../../data/sample_input2.txt");
+        let mut network = parse_network(inp).unwrap().1;
+        network.fast_forward(1_000_000_000);
+        assert_eq!(network.low_pulses(), 4_250_000_000);
+        assert_eq!(network.high_pulses(), 2_750_000_000);
+    }
 }